@@ -0,0 +1,32 @@
+use ark_ed_on_bls12_381::Fq;
+use ark_encryption::{EncryptCircuit, JubJub, JubJubVar, Parameters, Randomness};
+use ark_std::{test_rng, UniformRand};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+
+fn bench_encrypt_batch(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let params = Parameters::<JubJub>::default_multi(4);
+    let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+    let msgs = (0..256)
+        .map(|_| (0..4).map(|_| Fq::rand(&mut rng)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("encrypt 256 messages");
+    group.bench_function("serial", |b| {
+        b.iter(|| {
+            msgs.iter()
+                .map(|msg| Circuit::encrypt(&pk, msg, &Randomness::rand(&mut rng), &params))
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap()
+        })
+    });
+    group.bench_function("encrypt_batch", |b| {
+        b.iter(|| Circuit::encrypt_batch(&pk, &msgs, &params, &mut rng).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_encrypt_batch);
+criterion_main!(benches);
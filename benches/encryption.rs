@@ -0,0 +1,62 @@
+use ark_ed_on_bls12_381::Fq;
+use ark_encryption::{Bls12_381, EncryptCircuit, JubJub, JubJubVar, Parameters, Randomness};
+use ark_std::{test_rng, UniformRand};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+
+fn bench_keygen(c: &mut Criterion) {
+    let mut rng = test_rng();
+    c.bench_function("keygen", |b| b.iter(|| Circuit::keygen(&mut rng).unwrap()));
+}
+
+fn bench_encrypt_decrypt(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+
+    let mut group = c.benchmark_group("encryption");
+    for n in [1, 4, 16, 64] {
+        let params = Parameters::<JubJub>::default_multi(n);
+        let msg = (0..n).map(|_| Fq::rand(&mut rng)).collect::<Vec<_>>();
+        let r = Randomness::rand(&mut rng);
+        let cipher = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("encrypt", n), &msg, |b, msg| {
+            b.iter(|| Circuit::encrypt(&pk, msg, &r, &params).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decrypt", n), &cipher, |b, cipher| {
+            b.iter(|| Circuit::decrypt(cipher.clone(), &sk, &params).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn bench_groth16(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let params = Parameters::<JubJub>::default_multi(1);
+    let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+    let circuit = Circuit::new(pk, vec![Fq::rand(&mut rng)], params.clone(), &mut rng).unwrap();
+    let (proving_key, verifying_key) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+    let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+
+    let mut group = c.benchmark_group("groth16");
+    group.sample_size(10);
+    group.bench_function("prove", |b| {
+        b.iter(|| circuit.prove(&proving_key, &mut rng).unwrap())
+    });
+    group.bench_function("verify", |b| {
+        b.iter(|| {
+            Circuit::verify(
+                &verifying_key,
+                &circuit.resulted_ciphertext,
+                &proof,
+                &params,
+            )
+            .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_keygen, bench_encrypt_decrypt, bench_groth16);
+criterion_main!(benches);
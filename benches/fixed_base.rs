@@ -0,0 +1,26 @@
+use ark_ec::ProjectiveCurve;
+use ark_ed_on_bls12_381::Fr;
+use ark_encryption::{JubJub, PrecomputedGenerator};
+use ark_std::ops::MulAssign;
+use ark_std::{test_rng, UniformRand};
+use criterion::{criterion_group, criterion_main, Criterion};
+
+fn bench_generator_mul(c: &mut Criterion) {
+    let mut rng = test_rng();
+    let scalar = Fr::rand(&mut rng);
+    let table = PrecomputedGenerator::<JubJub>::default();
+
+    let mut group = c.benchmark_group("generator multiplication");
+    group.bench_function("naive", |b| {
+        b.iter(|| {
+            let mut p = JubJub::prime_subgroup_generator();
+            p.mul_assign(scalar);
+            p
+        })
+    });
+    group.bench_function("precomputed table", |b| b.iter(|| table.mul(&scalar)));
+    group.finish();
+}
+
+criterion_group!(benches, bench_generator_mul);
+criterion_main!(benches);
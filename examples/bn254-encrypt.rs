@@ -0,0 +1,29 @@
+use ark_bn254::Bn254 as ProjectiveEngine;
+use ark_ed_on_bn254::{constraints::EdwardsVar as CurveVar, EdwardsProjective as Curve, Fq};
+use ark_encryption::{EncryptCircuit, Parameters};
+use ark_groth16::Groth16;
+use ark_snark::CircuitSpecificSetupSNARK;
+use ark_std::test_rng;
+
+type Circuit = EncryptCircuit<Curve, CurveVar>;
+
+fn main() {
+    let mut rng = test_rng();
+    let msg = vec![Fq::from(42u64), Fq::from(7u64)];
+
+    let params = Parameters::<Curve>::default_multi(2);
+    let (sk, pub_key) = Circuit::keygen(&mut rng).unwrap();
+
+    let circuit = Circuit::new(pub_key, msg.clone(), params.clone(), &mut rng).unwrap();
+    let (pk, vk) = Groth16::<ProjectiveEngine>::setup(circuit, &mut rng).unwrap();
+
+    let circuit = Circuit::new(pub_key, msg.clone(), params.clone(), &mut rng).unwrap();
+    let enc = circuit.resulted_ciphertext.clone();
+    let proof = circuit.prove(&pk, &mut rng).unwrap();
+
+    let valid_proof = Circuit::verify(&vk, &enc, &proof, &params).unwrap();
+    assert!(valid_proof);
+
+    let decrypted = Circuit::decrypt(enc, &sk, &params).unwrap();
+    assert_eq!(decrypted, msg);
+}
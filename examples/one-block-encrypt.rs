@@ -1,14 +1,12 @@
-use ark_std::test_rng;
-use ark_bls12_381::{Bls12_381 as ProjectiveEngine};
-use ark_ed_on_bls12_381::{
-    constraints::EdwardsVar as CurveVar, EdwardsProjective as Curve, Fq,
-};
+use ark_bls12_381::Bls12_381 as ProjectiveEngine;
+use ark_ed_on_bls12_381::{constraints::EdwardsVar as CurveVar, EdwardsProjective as Curve, Fq};
+use ark_encryption::{poseidon, EncryptCircuit, Parameters};
 use ark_ff::Field;
 use ark_groth16::Groth16;
 use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
-use ark_encryption::{EncryptCircuit, Parameters, poseidon};
+use ark_std::test_rng;
 
-type Circuit = EncryptCircuit::<Curve, CurveVar>;
+type Circuit = EncryptCircuit<Curve, CurveVar>;
 
 fn main() {
     let mut rng = test_rng();
@@ -17,20 +15,16 @@ fn main() {
 
     let params = Parameters::<Curve> {
         n: 1,
-        poseidon: poseidon::get_poseidon_params::<Curve>(2),
+        sponge: poseidon::get_poseidon_params::<Curve>(2),
+        ..Parameters::default()
     };
     let (_, pub_key) = Circuit::keygen(&mut rng).unwrap();
 
-    let circuit = Circuit::new(
-        pub_key.clone(),
-        msg.clone().into(),
-        params.clone(),
-        &mut rng,
-    ).unwrap();
+    let circuit = Circuit::new(pub_key, msg.clone(), params.clone(), &mut rng).unwrap();
 
     let (pk, vk) = Groth16::<ProjectiveEngine>::setup(circuit, &mut rng).unwrap();
 
-    let circuit = Circuit::new(pub_key, msg.clone().into(), params.clone(), &mut rng).unwrap();
+    let circuit = Circuit::new(pub_key, msg, params.clone(), &mut rng).unwrap();
     let enc = circuit.resulted_ciphertext.clone();
     let proof = Groth16::prove(&pk, circuit, &mut rng).unwrap();
 
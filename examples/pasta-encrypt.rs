@@ -0,0 +1,25 @@
+use ark_encryption::{EncryptCircuit, Pallas, PallasVar, Parameters, Randomness};
+use ark_pallas::Fq;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+use ark_std::{test_rng, UniformRand};
+
+type Circuit = EncryptCircuit<Pallas, PallasVar>;
+
+fn main() {
+    let mut rng = test_rng();
+    let msg = vec![Fq::from(42u64), Fq::from(7u64)];
+
+    let params = Parameters::<Pallas>::default_multi(2);
+    let (sk, pub_key) = Circuit::keygen(&mut rng).unwrap();
+
+    let enc = Circuit::encrypt(&pub_key, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+    let decrypted = Circuit::decrypt(enc, &sk, &params).unwrap();
+    assert_eq!(decrypted, msg);
+
+    // Pallas has no pairing-friendly partner, so the circuit is only checked for
+    // satisfiability here, proving it is left to a proof system over Vesta
+    let circuit = Circuit::new(pub_key, msg, params, &mut rng).unwrap();
+    let cs = ConstraintSystem::<Fq>::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    assert!(cs.is_satisfied().unwrap());
+}
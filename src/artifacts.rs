@@ -0,0 +1,112 @@
+//! Byte encodings of the Groth16 artifacts produced for [`EncryptCircuit`](crate::EncryptCircuit).
+//!
+//! Every encoding starts with a two-byte header, [`ARTIFACTS_VERSION`] followed by a byte
+//! telling proofs, proving keys and verifying keys apart, and continues with the compressed
+//! canonical serialization of the artifact.
+
+use crate::EncryptionError;
+use ark_ec::PairingEngine;
+use ark_groth16::{Proof, ProvingKey, VerifyingKey};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::{vec, vec::Vec};
+
+/// Version of the encoding, bumped whenever the layout changes.
+pub const ARTIFACTS_VERSION: u8 = 1;
+
+const PROOF_KIND: u8 = 0;
+const PROVING_KEY_KIND: u8 = 1;
+const VERIFYING_KEY_KIND: u8 = 2;
+
+pub fn serialize_proof<E: PairingEngine>(proof: &Proof<E>) -> Result<Vec<u8>, EncryptionError> {
+    to_bytes(PROOF_KIND, proof)
+}
+
+pub fn deserialize_proof<E: PairingEngine>(bytes: &[u8]) -> Result<Proof<E>, EncryptionError> {
+    from_bytes(PROOF_KIND, bytes)
+}
+
+pub fn serialize_pk<E: PairingEngine>(pk: &ProvingKey<E>) -> Result<Vec<u8>, EncryptionError> {
+    to_bytes(PROVING_KEY_KIND, pk)
+}
+
+pub fn deserialize_pk<E: PairingEngine>(bytes: &[u8]) -> Result<ProvingKey<E>, EncryptionError> {
+    from_bytes(PROVING_KEY_KIND, bytes)
+}
+
+pub fn serialize_vk<E: PairingEngine>(vk: &VerifyingKey<E>) -> Result<Vec<u8>, EncryptionError> {
+    to_bytes(VERIFYING_KEY_KIND, vk)
+}
+
+pub fn deserialize_vk<E: PairingEngine>(bytes: &[u8]) -> Result<VerifyingKey<E>, EncryptionError> {
+    from_bytes(VERIFYING_KEY_KIND, bytes)
+}
+
+fn to_bytes<T: CanonicalSerialize>(kind: u8, value: &T) -> Result<Vec<u8>, EncryptionError> {
+    let mut bytes = vec![ARTIFACTS_VERSION, kind];
+    value.serialize(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn from_bytes<T: CanonicalDeserialize>(kind: u8, bytes: &[u8]) -> Result<T, EncryptionError> {
+    match bytes {
+        [ARTIFACTS_VERSION, k, body @ ..] if *k == kind => {
+            let mut body = body;
+            let value = T::deserialize(&mut body)?;
+            if !body.is_empty() {
+                return Err(SerializationError::InvalidData.into());
+            }
+            Ok(value)
+        }
+        [version, k, ..] => Err(EncryptionError::UnsupportedArtifact {
+            version: *version,
+            kind: *k,
+        }),
+        _ => Err(SerializationError::NotEnoughSpace.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{Bls12_381, JubJub, Parameters};
+    use ark_std::test_rng;
+
+    #[test]
+    fn reloaded_artifacts_verify_the_proof() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let circuit = Circuit::new(pk, message(1), params.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let proving_key =
+            deserialize_pk::<Bls12_381>(&serialize_pk(&proving_key).unwrap()).unwrap();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+
+        let proof_bytes = serialize_proof(&proof).unwrap();
+        let vk_bytes = serialize_vk(&vk).unwrap();
+        assert_eq!(proof_bytes[..2], [ARTIFACTS_VERSION, PROOF_KIND]);
+        let proof = deserialize_proof::<Bls12_381>(&proof_bytes).unwrap();
+        let vk = deserialize_vk::<Bls12_381>(&vk_bytes).unwrap();
+        assert!(Circuit::verify(&vk, &cipher, &proof, &params).unwrap());
+
+        let mut wrong_version = proof_bytes.clone();
+        wrong_version[0] = ARTIFACTS_VERSION + 1;
+        assert!(matches!(
+            deserialize_proof::<Bls12_381>(&wrong_version),
+            Err(EncryptionError::UnsupportedArtifact { version, kind: PROOF_KIND })
+                if version == ARTIFACTS_VERSION + 1
+        ));
+        assert!(matches!(
+            deserialize_vk::<Bls12_381>(&proof_bytes),
+            Err(EncryptionError::UnsupportedArtifact { .. })
+        ));
+        let mut trailing = proof_bytes.clone();
+        trailing.push(0);
+        assert!(deserialize_proof::<Bls12_381>(&trailing).is_err());
+        assert!(deserialize_proof::<Bls12_381>(&proof_bytes[..proof_bytes.len() - 1]).is_err());
+        assert!(deserialize_proof::<Bls12_381>(&[ARTIFACTS_VERSION]).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Bandersnatch, the twisted Edwards curve of Masson, Sanso and Zhang over the BLS12-381 scalar
+//! field, see <https://eprint.iacr.org/2021/1152>.
+//!
+//! The `ark-ed-on-bls12-381-bandersnatch` crate only exists for the 0.4 line of arkworks, so the
+//! curve is defined here on top of the 0.3 traits. It shares its base field with JubJub, so it
+//! uses the same Poseidon parameters and proves with Groth16 over [`crate::Bls12_381`].
+
+use ark_ec::{
+    models::{ModelParameters, MontgomeryModelParameters, TEModelParameters},
+    twisted_edwards_extended::{GroupAffine, GroupProjective},
+};
+use ark_ff::{
+    biginteger::BigInteger256 as BigInteger,
+    field_new,
+    fields::{FftParameters, Field, Fp256, Fp256Parameters, FpParameters},
+};
+use ark_r1cs_std::{fields::fp::FpVar, groups::curves::twisted_edwards::AffineVar};
+
+pub use ark_ed_on_bls12_381::Fq;
+
+pub type Fr = Fp256<FrParameters>;
+
+pub struct FrParameters;
+
+impl Fp256Parameters for FrParameters {}
+impl FftParameters for FrParameters {
+    type BigInt = BigInteger;
+
+    const TWO_ADICITY: u32 = 5;
+
+    #[rustfmt::skip]
+    const TWO_ADIC_ROOT_OF_UNITY: BigInteger = BigInteger([
+        0x4b263b9a8d79c573,
+        0xeadb3d0a007af1fd,
+        0xa54c8a4668832589,
+        0x610860c4254fb9d,
+    ]);
+}
+impl FpParameters for FrParameters {
+    /// MODULUS = 13108968793781547619861935127046491459309155893440570251786403306729687672801.
+    #[rustfmt::skip]
+    const MODULUS: BigInteger = BigInteger([
+        0x74fd06b52876e7e1,
+        0xff8f870074190471,
+        0xcce760202687600,
+        0x1cfb69d4ca675f52,
+    ]);
+
+    const MODULUS_BITS: u32 = 253;
+
+    const CAPACITY: u32 = Self::MODULUS_BITS - 1;
+
+    const REPR_SHAVE_BITS: u32 = 3;
+
+    #[rustfmt::skip]
+    const R: BigInteger = BigInteger([
+        0x5817ca56bc48c0f8,
+        0x383c7fc5f37dc74,
+        0x998c4fefecbc4ff8,
+        0x1824b159acc5056f,
+    ]);
+
+    #[rustfmt::skip]
+    const R2: BigInteger = BigInteger([
+        0xdbb4f5d658db47cb,
+        0x40fa7ca27fecb938,
+        0xaa9e6daec0055cea,
+        0xae793ddb14aec7d,
+    ]);
+
+    const INV: u64 = 0xf19f22295cc063df;
+
+    /// GENERATOR = 7
+    #[rustfmt::skip]
+    const GENERATOR: BigInteger = BigInteger([
+        0x1fb566d55baabf63,
+        0x1accd4e45609f0f7,
+        0xf2cde1856d1be1c3,
+        0x1817c84bc55e4972,
+    ]);
+
+    const MODULUS_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        13438322763177358320,
+        9207542918679396920,
+        461402362329971456,
+        1044189607433056169,
+    ]);
+
+    const T: BigInteger = BigInteger([
+        10063267209553360703,
+        575471432417462307,
+        10405131189107246000,
+        65261850464566010,
+    ]);
+
+    const T_MINUS_ONE_DIV_TWO: BigInteger = BigInteger([
+        14255005641631456159,
+        287735716208731153,
+        5202565594553623000,
+        32630925232283005,
+    ]);
+}
+
+pub type EdwardsAffine = GroupAffine<EdwardsParameters>;
+pub type EdwardsProjective = GroupProjective<EdwardsParameters>;
+
+/// A variable that is the R1CS equivalent of [`EdwardsAffine`].
+pub type EdwardsVar = AffineVar<EdwardsParameters, FpVar<Fq>>;
+
+/// Bandersnatch's curve equation: -5x² + y² = 1 + dx²y², with
+///
+/// q = 52435875175126190479447740508185965837690552500527637822603658699938581184513.
+///
+/// a = -5.
+/// d = (138827208126141220649022263972958607803/171449701953573178309673572579671231137) mod q
+///   = 45022363124591815672509500913686876175488063829319466900776701791074614335719.
+///
+/// The group has order 4r for the 253-bit prime r of [`Fr`].
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct EdwardsParameters;
+
+impl ModelParameters for EdwardsParameters {
+    type BaseField = Fq;
+    type ScalarField = Fr;
+}
+
+impl TEModelParameters for EdwardsParameters {
+    /// COEFF_A = -5
+    #[rustfmt::skip]
+    const COEFF_A: Fq = field_new!(Fq, "-5");
+
+    /// COEFF_D = (138827208126141220649022263972958607803/171449701953573178309673572579671231137) mod q
+    #[rustfmt::skip]
+    const COEFF_D: Fq = field_new!(Fq, "45022363124591815672509500913686876175488063829319466900776701791074614335719");
+
+    /// COFACTOR = 4
+    const COFACTOR: &'static [u64] = &[4];
+
+    /// COFACTOR^(-1) mod r =
+    /// 9831726595336160714896451345284868594481866920080427688839802480047265754601
+    #[rustfmt::skip]
+    const COFACTOR_INV: Fr = field_new!(Fr, "9831726595336160714896451345284868594481866920080427688839802480047265754601");
+
+    /// AFFINE_GENERATOR_COEFFS = (GENERATOR_X, GENERATOR_Y)
+    const AFFINE_GENERATOR_COEFFS: (Self::BaseField, Self::BaseField) = (GENERATOR_X, GENERATOR_Y);
+
+    type MontgomeryModelParameters = EdwardsParameters;
+
+    /// Multiplication by `a` is multiplication by -5.
+    #[inline(always)]
+    fn mul_by_a(elem: &Self::BaseField) -> Self::BaseField {
+        let t = elem.double().double();
+        -(t + elem)
+    }
+}
+
+impl MontgomeryModelParameters for EdwardsParameters {
+    /// COEFF_A = 2(a + d)/(a - d)
+    #[rustfmt::skip]
+    const COEFF_A: Fq = field_new!(Fq, "29978822694968839326280996386011761570173833766074948509196803838190355340952");
+    /// COEFF_B = 4/(a - d)
+    #[rustfmt::skip]
+    const COEFF_B: Fq = field_new!(Fq, "25465760566081946422412445027709227188579564747101592991722834452325077642517");
+
+    type TEModelParameters = EdwardsParameters;
+}
+
+#[rustfmt::skip]
+const GENERATOR_X: Fq = field_new!(Fq, "18886178867200960497001835917649091219057080094937609519140440539760939937304");
+#[rustfmt::skip]
+const GENERATOR_Y: Fq = field_new!(Fq, "19188667384257783945677642223292697773471335439753913231509108946878080696678");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bls12_381, EncryptCircuit, Parameters};
+    use ark_ec::AffineCurve;
+    use ark_ff::{FftField, One, PrimeField, SquareRootField, Zero};
+    use ark_std::test_rng;
+
+    type Circuit = EncryptCircuit<EdwardsProjective, EdwardsVar>;
+
+    #[test]
+    fn the_generator_lies_in_the_prime_order_subgroup() {
+        let g = EdwardsAffine::prime_subgroup_generator();
+        assert!(g.is_on_curve());
+        assert!(g.is_in_correct_subgroup_assuming_on_curve());
+        assert!(!g.mul_by_cofactor().is_zero());
+
+        let x = GENERATOR_X;
+        assert_eq!(
+            EdwardsParameters::mul_by_a(&x),
+            x * <EdwardsParameters as TEModelParameters>::COEFF_A
+        );
+    }
+
+    #[test]
+    fn scalar_field_constants_are_consistent() {
+        assert_eq!(Fr::from(7u64), Fr::multiplicative_generator());
+        let root = Fr::two_adic_root_of_unity();
+        assert_eq!(root.pow([1u64 << 5]), Fr::one());
+        assert_ne!(root.pow([1u64 << 4]), Fr::one());
+        assert_eq!(Fr::from(4u64) * EdwardsParameters::COFACTOR_INV, Fr::one());
+        assert_eq!(
+            Fr::from(9u64).sqrt().map(|s| s.square()),
+            Some(Fr::from(9u64))
+        );
+        assert_eq!(
+            -Fr::one(),
+            Fr::from_repr(FrParameters::MODULUS_MINUS_ONE_DIV_TWO)
+                .unwrap()
+                .double()
+        );
+    }
+
+    #[test]
+    fn encrypts_decrypts_proves_and_verifies() {
+        let mut rng = test_rng();
+        let msg = vec![Fq::from(42u64), Fq::from(7u64)];
+        let params = Parameters::<EdwardsProjective>::default_multi(2);
+        let (sk, pub_key) = Circuit::keygen(&mut rng).unwrap();
+
+        let (pk, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let circuit = Circuit::new(pub_key, msg.clone(), params.clone(), &mut rng).unwrap();
+        let enc = circuit.resulted_ciphertext.clone();
+        let proof = circuit.prove(&pk, &mut rng).unwrap();
+
+        assert!(Circuit::verify(&vk, &enc, &proof, &params).unwrap());
+        assert_eq!(Circuit::decrypt(enc, &sk, &params).unwrap(), msg);
+    }
+}
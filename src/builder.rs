@@ -0,0 +1,209 @@
+//! Encryption and decryption bound to associated data that is absorbed in chunks, so that a
+//! large context doesn't have to be collected into one slice first.
+//!
+//! The KDF sponge keeps its state between chunks, so absorbing `a` and then `b` derives the
+//! same masks as [`EncryptCircuit::encrypt_with_aad`] with the concatenation of `a` and `b`.
+
+use crate::circuit::{
+    Ciphertext, EncryptCircuit, Parameters, Plaintext, PublicKey, Randomness, SecretKey,
+};
+use crate::sponge::{PoseidonHash, SpongeHash};
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_r1cs_std::groups::CurveVar;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::{Absorb, CryptographicSponge};
+use ark_std::marker::PhantomData;
+
+/// Encrypts one message under `r` to `pk`, with the associated data absorbed through
+/// [`EncryptionBuilder::absorb_aad`] beforehand.
+pub struct EncryptionBuilder<'a, C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    c1: C,
+    sponge: S::Sponge,
+    params: &'a Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<'a, C, CV, S> EncryptionBuilder<'a, C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pub fn new(
+        pk: &PublicKey<C>,
+        r: &Randomness<C>,
+        params: &'a Parameters<C, S>,
+    ) -> Result<Self, EncryptionError> {
+        EncryptCircuit::<C, CV, S>::validate_public_key(pk)?;
+        EncryptCircuit::<C, CV, S>::check_randomness(r)?;
+
+        let c1 = EncryptCircuit::<C, CV, S>::commit_c1(r, params);
+        let s = EncryptCircuit::<C, CV, S>::shared_secret(pk, &r.0);
+        let sponge = EncryptCircuit::<C, CV, S>::kdf_sponge(&s, Some((*pk, c1)), &[], params);
+
+        Ok(Self {
+            c1,
+            sponge,
+            params,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Absorbs the next chunk of the associated data.
+    pub fn absorb_aad(&mut self, chunk: &[C::BaseField]) {
+        self.sponge.absorb(&chunk);
+    }
+
+    /// Masks `msg` with the masks derived from all associated data absorbed so far.
+    pub fn encrypt(self, msg: &Plaintext<C>) -> Result<Ciphertext<C>, EncryptionError> {
+        if msg.len() > self.params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: self.params.n,
+                got: msg.len(),
+            });
+        }
+
+        let masks =
+            EncryptCircuit::<C, CV, S>::slot_masks_of(&self.sponge, 0..msg.len(), self.params);
+        let c2 = msg.iter().zip(masks).map(|(m, dh)| dh + m).collect();
+        Ok((self.c1, c2))
+    }
+}
+
+/// Decrypts one ciphertext with `sk`, with the associated data absorbed through
+/// [`DecryptionBuilder::absorb_aad`] beforehand.
+pub struct DecryptionBuilder<'a, C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    cipher: Ciphertext<C>,
+    sponge: S::Sponge,
+    params: &'a Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<'a, C, CV, S> DecryptionBuilder<'a, C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pub fn new(cipher: Ciphertext<C>, sk: &SecretKey<C>, params: &'a Parameters<C, S>) -> Self {
+        let s = EncryptCircuit::<C, CV, S>::shared_secret(&cipher.0, &sk.0);
+        let binding = EncryptCircuit::<C, CV, S>::decryption_binding(&cipher.0, sk, params);
+        let sponge = EncryptCircuit::<C, CV, S>::kdf_sponge(&s, binding, &[], params);
+
+        Self {
+            cipher,
+            sponge,
+            params,
+            _curve_var: PhantomData,
+        }
+    }
+
+    /// Absorbs the next chunk of the associated data.
+    pub fn absorb_aad(&mut self, chunk: &[C::BaseField]) {
+        self.sponge.absorb(&chunk);
+    }
+
+    /// Unmasks the ciphertext with the masks derived from all associated data absorbed so far,
+    /// which yields a wrong plaintext unless it matches the data used for encryption.
+    pub fn decrypt(self) -> Result<Plaintext<C>, EncryptionError> {
+        let c2 = &self.cipher.1;
+        let masks =
+            EncryptCircuit::<C, CV, S>::slot_masks_of(&self.sponge, 0..c2.len(), self.params);
+        Ok(c2.iter().zip(masks).map(|(c2i, dh)| *c2i - dh).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{JubJub, JubJubVar};
+    use ark_ed_on_bls12_381::{Fq, Fr};
+    use ark_ff::{One, Zero};
+    use ark_std::vec::Vec;
+    use ark_std::{test_rng, UniformRand};
+
+    type Encryption<'a> = EncryptionBuilder<'a, JubJub, JubJubVar>;
+    type Decryption<'a> = DecryptionBuilder<'a, JubJub, JubJubVar>;
+
+    #[test]
+    fn chunked_associated_data_round_trips_like_one_absorption() {
+        let mut rng = test_rng();
+        let aad = (0..1000).map(|_| Fq::rand(&mut rng)).collect::<Vec<_>>();
+        for bind_key in [false, true] {
+            let params = Parameters {
+                bind_key,
+                ..Parameters::<JubJub>::default_multi(3)
+            };
+            let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+            let r = Randomness::rand(&mut rng);
+            let msg = message(3);
+
+            let mut builder = Encryption::new(&pk, &r, &params).unwrap();
+            for chunk in aad.chunks(64) {
+                builder.absorb_aad(chunk);
+            }
+            let cipher = builder.encrypt(&msg).unwrap();
+            assert_eq!(
+                cipher,
+                Circuit::encrypt_with_aad(&pk, &msg, &aad, &r, &params).unwrap()
+            );
+
+            let mut decryption = Decryption::new(cipher.clone(), &sk, &params);
+            for chunk in aad.chunks(300) {
+                decryption.absorb_aad(chunk);
+            }
+            assert_eq!(decryption.decrypt().unwrap(), msg);
+
+            let mut wrong = Decryption::new(cipher, &sk, &params);
+            wrong.absorb_aad(&aad[1..]);
+            assert_ne!(wrong.decrypt().unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn identity_keys_zero_randomness_and_long_messages_are_errors() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+
+        assert!(matches!(
+            Encryption::new(&JubJub::zero(), &r, &params),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        assert!(matches!(
+            Encryption::new(&pk, &Randomness::from_scalar(Fr::zero()), &params),
+            Err(EncryptionError::ZeroRandomness)
+        ));
+        let mut builder = Encryption::new(&pk, &r, &params).unwrap();
+        builder.absorb_aad(&[Fq::one()]);
+        assert!(matches!(
+            builder.encrypt(&message(3)),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+    }
+}
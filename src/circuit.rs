@@ -1,51 +1,160 @@
-use crate::poseidon::get_poseidon_params;
-use anyhow::anyhow;
+use crate::fixed_base::PrecomputedGenerator;
+use crate::poseidon::{
+    default_alpha, generate_poseidon_params, get_poseidon_params, get_poseidon_params_for,
+    get_wide_poseidon_params, is_mds, poseidon_params_parts, validate_poseidon_config,
+    POSEIDON_RATE, POSEIDON_SECURITY_BITS,
+};
+use crate::sponge::{PoseidonHash, SpongeHash, WidePoseidonHash};
+use crate::EncryptionError;
 use ark_ec::{PairingEngine, ProjectiveCurve};
-use ark_ff::{to_bytes, BigInteger, BitIteratorLE, Field, PrimeField, ToConstraintField, Zero};
+use ark_ff::{to_bytes, BigInteger, Field, One, PrimeField, ToConstraintField, Zero};
 use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
 use ark_r1cs_std::fields::fp::FpVar;
 use ark_r1cs_std::groups::CurveVar;
 use ark_r1cs_std::prelude::*;
-use ark_r1cs_std::ToConstraintFieldGadget;
 use ark_relations::ns;
 use ark_relations::r1cs::{
-    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError,
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, Namespace, SynthesisError,
 };
-use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_snark::SNARK;
 use ark_sponge::constraints::{AbsorbGadget, CryptographicSpongeVar};
-use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
-use ark_sponge::poseidon::{PoseidonParameters, PoseidonSponge};
-use ark_sponge::{Absorb, CryptographicSponge, FieldBasedCryptographicSponge};
+use ark_sponge::poseidon::PoseidonParameters;
+use ark_sponge::{Absorb, CryptographicSponge};
+use ark_std::fmt::Debug;
 use ark_std::marker::PhantomData;
-use ark_std::rand::{CryptoRng, Rng, RngCore};
+use ark_std::num::NonZeroUsize;
+use ark_std::ops::{Deref, Range};
+use ark_std::rand::{CryptoRng, Rng, RngCore, SeedableRng};
+use ark_std::str::FromStr;
 use ark_std::vec::Vec;
-use ark_std::UniformRand;
-use std::borrow::Borrow;
-use std::cmp::Ordering;
-use std::fmt::Debug;
-use std::str::FromStr;
+use ark_std::{cfg_iter, format, vec, UniformRand};
+use blake2::{Blake2s, Digest};
+use rand_chacha::ChaCha20Rng;
+use subtle::ConstantTimeEq;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
 
-pub struct EncryptCircuit<C, CV>
+#[derive(Clone)]
+pub struct EncryptCircuit<C, CV, S = PoseidonHash>
 where
     C: ProjectiveCurve,
     C::BaseField: PrimeField,
     CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
 {
     r: Randomness<C>,
     msg: Plaintext<C>,
     pk: PublicKey<C>,
+    aad: Vec<C::BaseField>,
     pub resulted_ciphertext: Ciphertext<C>,
-    params: Parameters<C>,
-    _curve_var: PhantomData<CV>,
+    params: Parameters<C, S>,
+    length_prefixed: bool,
+    pk_is_public: bool,
+    plaintext_is_public: bool,
+    range_bits: Option<usize>,
+    // the gadget only matters at synthesis, so it shouldn't keep circuits from crossing threads
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+// the randomness and the plaintext are as sensitive as a secret key, so only the public parts
+// of the circuit are printed
+impl<C, CV, S> Debug for EncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
+        f.debug_struct("EncryptCircuit")
+            .field("n", &self.params.n)
+            .field("pk", &self.pk.into_affine())
+            .field("ciphertext_slots", &self.resulted_ciphertext.1.len())
+            .field("r", &"<redacted>")
+            .field("msg", &"<redacted>")
+            .finish()
+    }
+}
+
+/// How the KDF derives the mask `dh_i` of slot `i` from the sponge that has absorbed the
+/// shared secret.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MaskDerivation {
+    /// `dh_i = H(s, i)`, a fresh copy of the sponge absorbing the slot index, so that any
+    /// slot can be derived on its own at the cost of one permutation per slot.
+    #[default]
+    Indexed,
+    /// `dh_i` is the `i`-th element squeezed off the sponge, which takes a permutation per
+    /// `rate` slots but requires squeezing all slots up to the last one needed.
+    Squeezed,
+    /// `dh_i` is element `i mod k` squeezed off a fresh copy of the sponge absorbing the chunk
+    /// index `i / k`, for the width `k`, so that one absorption masks `k` slots while a slot
+    /// only requires squeezing up to it within its own chunk. `Chunked(1)` derives the same
+    /// masks as `Indexed`.
+    Chunked(NonZeroUsize),
+}
+
+// tagged by a byte, followed by the width for `Chunked`
+impl CanonicalSerialize for MaskDerivation {
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        match self {
+            Self::Indexed => 0u8.serialize(&mut writer),
+            Self::Squeezed => 1u8.serialize(&mut writer),
+            Self::Chunked(width) => {
+                2u8.serialize(&mut writer)?;
+                (width.get() as u64).serialize(&mut writer)
+            }
+        }
+    }
+
+    fn serialized_size(&self) -> usize {
+        match self {
+            Self::Indexed | Self::Squeezed => 0u8.serialized_size(),
+            Self::Chunked(width) => 0u8.serialized_size() + (width.get() as u64).serialized_size(),
+        }
+    }
+}
+
+impl CanonicalDeserialize for MaskDerivation {
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        match u8::deserialize(&mut reader)? {
+            0 => Ok(Self::Indexed),
+            1 => Ok(Self::Squeezed),
+            2 => usize::try_from(u64::deserialize(&mut reader)?)
+                .ok()
+                .and_then(NonZeroUsize::new)
+                .map(Self::Chunked)
+                .ok_or(SerializationError::InvalidData),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
-pub struct Parameters<C: ProjectiveCurve>
+pub struct Parameters<C: ProjectiveCurve, S = PoseidonHash>
 where
     C::BaseField: PrimeField,
+    S: SpongeHash<C::BaseField>,
 {
+    /// Number of message slots the circuits are synthesized for. The native encryption and
+    /// decryption work on messages of any length, `n` only bounds the ones that can be proven.
     pub n: usize,
-    pub poseidon: PoseidonParameters<C::BaseField>,
+    pub sponge: S::Parameters,
+    /// Tag of the deployment that is absorbed into the KDF after the shared secret and
+    /// taken by [`EncryptCircuit`] as public input, so that neither ciphertexts nor proofs carry
+    /// over to parameters with a different tag.
+    pub domain_tag: Option<C::BaseField>,
+    /// Whether the KDF absorbs the recipient's public key and `c1` right after the shared
+    /// secret, which binds every ciphertext to the key it was created for.
+    pub bind_key: bool,
+    pub mask_derivation: MaskDerivation,
+    /// Base point of the keys and of `c1`, the prime-subgroup generator unless set through
+    /// [`Parameters::with_generator`].
+    pub generator: C,
 }
 
 impl<C: ProjectiveCurve> Parameters<C>
@@ -56,7 +165,288 @@ where
     pub fn default_multi(n: usize) -> Self {
         Self {
             n,
-            poseidon: get_poseidon_params::<C>(2),
+            sponge: Self::default_sponge(),
+            domain_tag: None,
+            bind_key: false,
+            mask_derivation: MaskDerivation::Indexed,
+            generator: C::prime_subgroup_generator(),
+        }
+    }
+
+    // Poseidon parameters at the rate of `ark-sponge` for 128 bits of security
+    fn default_sponge() -> PoseidonParameters<C::BaseField> {
+        get_poseidon_params_for::<C>(POSEIDON_RATE, POSEIDON_SECURITY_BITS as usize)
+            .expect("128-bit security is supported at the default rate")
+    }
+
+    /// Constructs parameters for a Poseidon sponge of the given rate.
+    ///
+    /// Only a rate of 2 is accepted: both the native and the in-circuit sponge of
+    /// `ark-sponge` v0.3 hardcode it, so any other rate returns
+    /// [`EncryptionError::InvalidParameters`] rather than being silently ignored. Other rates
+    /// are supported by the [`WidePoseidonHash`] sponge, see [`Parameters::with_wide_rate`].
+    pub fn with_rate(n: usize, rate: usize) -> Result<Self, EncryptionError> {
+        if rate != POSEIDON_RATE {
+            return Err(EncryptionError::InvalidParameters(format!(
+                "unsupported Poseidon rate {rate}, only {POSEIDON_RATE} is supported, \
+                 other rates need the WidePoseidonHash sponge"
+            )));
+        }
+
+        Ok(Self {
+            n,
+            sponge: get_poseidon_params::<C>(rate),
+            domain_tag: None,
+            bind_key: false,
+            mask_derivation: MaskDerivation::Indexed,
+            generator: C::prime_subgroup_generator(),
+        })
+    }
+
+    /// Starts building parameters with a custom Poseidon configuration, see [`ParametersBuilder`].
+    pub fn builder() -> ParametersBuilder<C> {
+        ParametersBuilder::default()
+    }
+}
+
+impl<C: ProjectiveCurve> Parameters<C, WidePoseidonHash>
+where
+    C::BaseField: PrimeField,
+    <C::BaseField as FromStr>::Err: Debug,
+{
+    /// Constructs parameters for a [`WidePoseidonHash`] sponge of any non-zero rate, with the
+    /// round numbers of [`get_wide_poseidon_params`]. A wider sponge absorbs the shared secret
+    /// and the slot index in fewer permutations, at rate 2 it derives the same masks as
+    /// [`Parameters::default_multi`].
+    pub fn with_wide_rate(n: usize, rate: usize) -> Result<Self, EncryptionError> {
+        Ok(Self {
+            n,
+            sponge: get_wide_poseidon_params::<C>(rate)?,
+            domain_tag: None,
+            bind_key: false,
+            mask_derivation: MaskDerivation::Indexed,
+            generator: C::prime_subgroup_generator(),
+        })
+    }
+}
+
+impl<C: ProjectiveCurve> Parameters<C>
+where
+    C::BaseField: PrimeField,
+{
+    /// Checks the Poseidon configuration against the round bounds of
+    /// [`validate_poseidon_config`] and that its matrix is MDS with round constants for every
+    /// round, which is what custom or deserialized parameters should be held to.
+    pub fn validate(&self) -> Result<(), EncryptionError> {
+        let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(&self.sponge);
+        validate_poseidon_config::<C::BaseField>(
+            POSEIDON_RATE,
+            full_rounds as usize,
+            partial_rounds as usize,
+            alpha,
+        )?;
+
+        if !is_mds(&mds) {
+            return Err(EncryptionError::InvalidParameters(
+                "the Poseidon matrix isn't MDS".into(),
+            ));
+        }
+        if self.generator.is_zero() || !in_prime_order_subgroup(&self.generator) {
+            return Err(EncryptionError::PointNotOnCurve);
+        }
+        let width = POSEIDON_RATE + 1;
+        if ark.len() != (full_rounds + partial_rounds) as usize
+            || ark.iter().any(|row| row.len() != width)
+        {
+            return Err(EncryptionError::InvalidParameters(
+                "the Poseidon round constants don't match the number of rounds".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Blake2s digest of the canonical serialization of the parameters, which covers `n`, the
+    /// Poseidon configuration and every other setting the masks depend on. Parties can compare
+    /// digests up front, as mismatched parameters only show as garbage plaintexts otherwise.
+    ///
+    /// The serialization is little-endian with fixed-width integers, so the digest doesn't
+    /// depend on the platform.
+    pub fn compat_hash(&self) -> [u8; 32] {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        self.serialize(&mut bytes)
+            .expect("serializing into a vector can't fail");
+        Blake2s::digest(&bytes).into()
+    }
+
+    /// Number of Poseidon permutations [`EncryptCircuit::encrypt`] runs to mask a message of
+    /// `msg_len` slots, with the circuit deriving masks for all slots at the cost of
+    /// `permutation_count(self.n)`.
+    ///
+    /// The sponge permutes once a full rate of absorbed elements is followed by more input and
+    /// when it switches from absorbing to squeezing, then once per further rate of output.
+    pub fn permutation_count(&self, msg_len: usize) -> usize
+    where
+        C::Affine: Absorb,
+    {
+        let point_len = C::zero()
+            .into_affine()
+            .to_sponge_field_elements_as_vec::<C::BaseField>()
+            .len();
+        // s, then pk and c1 when bound, then the domain tag
+        let absorbed =
+            point_len * if self.bind_key { 3 } else { 1 } + self.domain_tag.is_some() as usize;
+        let absorbing = absorbed.saturating_sub(1) / POSEIDON_RATE;
+
+        // the index starts a new rate if the absorbed elements filled the last one, then each
+        // rate of the chunk's masks takes a permutation
+        let rate_full = absorbed > 0 && absorbed.is_multiple_of(POSEIDON_RATE);
+        let chunk_cost = |width: usize| rate_full as usize + width.div_ceil(POSEIDON_RATE);
+        let masks = match self.mask_derivation {
+            MaskDerivation::Indexed => msg_len * chunk_cost(1),
+            // even squeezing no element switches the sponge to squeezing
+            MaskDerivation::Squeezed => msg_len.max(1).div_ceil(POSEIDON_RATE),
+            MaskDerivation::Chunked(width) => (0..msg_len)
+                .step_by(width.get())
+                .map(|start| chunk_cost(width.get().min(msg_len - start)))
+                .sum(),
+        };
+        absorbing + masks
+    }
+}
+
+impl<C: ProjectiveCurve, S: SpongeHash<C::BaseField>> Parameters<C, S>
+where
+    C::BaseField: PrimeField,
+{
+    /// Replaces the prime-subgroup generator by `generator`, e.g. a nothing-up-my-sleeve point,
+    /// failing with `PointNotOnCurve` unless it's a non-zero point of the prime-order subgroup.
+    ///
+    /// Keys have to be generated for the same generator, see
+    /// [`EncryptCircuit::keygen_with_params`].
+    pub fn with_generator(mut self, generator: C) -> Result<Self, EncryptionError> {
+        if generator.is_zero() || !in_prime_order_subgroup(&generator) {
+            return Err(EncryptionError::PointNotOnCurve);
+        }
+
+        self.generator = generator;
+        Ok(self)
+    }
+
+    /// Returns a fresh native sponge over these parameters, the same one the KDF starts from,
+    /// for protocols that hash their own data consistently with the encryption.
+    pub fn new_sponge(&self) -> S::Sponge {
+        S::Sponge::new(&self.sponge)
+    }
+
+    /// In-circuit counterpart of [`Parameters::new_sponge`].
+    pub fn new_sponge_var(&self, cs: ConstraintSystemRef<C::BaseField>) -> S::SpongeVar {
+        S::SpongeVar::new(cs, &self.sponge)
+    }
+}
+
+/// Builder for [`Parameters`] whose Poseidon constants are generated for the configured rounds
+/// instead of taken from the built-in table.
+///
+/// Unset values default to a single slot, rate 2, 8 full and 57 partial rounds and the smallest
+/// S-box exponent that is a permutation of the base field.
+#[derive(Clone, Debug)]
+pub struct ParametersBuilder<C: ProjectiveCurve> {
+    n: usize,
+    rate: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: Option<u64>,
+    domain_tag: Option<C::BaseField>,
+    bind_key: bool,
+    mask_derivation: MaskDerivation,
+    generator: Option<C>,
+    _curve: PhantomData<C>,
+}
+
+impl<C: ProjectiveCurve> Default for ParametersBuilder<C> {
+    fn default() -> Self {
+        Self {
+            n: 1,
+            rate: POSEIDON_RATE,
+            full_rounds: 8,
+            partial_rounds: 57,
+            alpha: None,
+            domain_tag: None,
+            bind_key: false,
+            mask_derivation: MaskDerivation::Indexed,
+            generator: None,
+            _curve: PhantomData,
+        }
+    }
+}
+
+impl<C: ProjectiveCurve> ParametersBuilder<C>
+where
+    C::BaseField: PrimeField,
+{
+    pub fn n(mut self, n: usize) -> Self {
+        self.n = n;
+        self
+    }
+
+    pub fn poseidon_rate(mut self, rate: usize) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn poseidon_full_rounds(mut self, full_rounds: usize) -> Self {
+        self.full_rounds = full_rounds;
+        self
+    }
+
+    pub fn poseidon_partial_rounds(mut self, partial_rounds: usize) -> Self {
+        self.partial_rounds = partial_rounds;
+        self
+    }
+
+    pub fn poseidon_alpha(mut self, alpha: u64) -> Self {
+        self.alpha = Some(alpha);
+        self
+    }
+
+    pub fn domain_tag(mut self, tag: C::BaseField) -> Self {
+        self.domain_tag = Some(tag);
+        self
+    }
+
+    pub fn bind_key(mut self, bind_key: bool) -> Self {
+        self.bind_key = bind_key;
+        self
+    }
+
+    pub fn mask_derivation(mut self, mask_derivation: MaskDerivation) -> Self {
+        self.mask_derivation = mask_derivation;
+        self
+    }
+
+    /// Replaces the prime-subgroup generator, which is checked by [`ParametersBuilder::build`].
+    pub fn generator(mut self, generator: C) -> Self {
+        self.generator = Some(generator);
+        self
+    }
+
+    /// Validates the Poseidon configuration and generates its round constants and MDS matrix.
+    pub fn build(self) -> Result<Parameters<C>, EncryptionError> {
+        let alpha = self.alpha.unwrap_or_else(default_alpha::<C::BaseField>);
+        let poseidon =
+            generate_poseidon_params(self.rate, self.full_rounds, self.partial_rounds, alpha)?;
+
+        let params = Parameters {
+            n: self.n,
+            sponge: poseidon,
+            domain_tag: self.domain_tag,
+            bind_key: self.bind_key,
+            mask_derivation: self.mask_derivation,
+            generator: C::prime_subgroup_generator(),
+        };
+        match self.generator {
+            Some(generator) => params.with_generator(generator),
+            None => Ok(params),
         }
     }
 }
@@ -69,73 +459,500 @@ where
     fn default() -> Self {
         Self {
             n: 1,
-            poseidon: get_poseidon_params::<C>(2),
+            sponge: Self::default_sponge(),
+            domain_tag: None,
+            bind_key: false,
+            mask_derivation: MaskDerivation::Indexed,
+            generator: C::prime_subgroup_generator(),
+        }
+    }
+}
+
+// n, followed by the full and partial rounds, the S-box exponent, the MDS matrix and the
+// round constants of the Poseidon configuration and finally the domain tag, the key binding flag,
+// the mask derivation and the generator
+impl<C: ProjectiveCurve> CanonicalSerialize for Parameters<C>
+where
+    C::BaseField: PrimeField,
+{
+    fn serialize<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(&self.sponge);
+        (self.n as u64).serialize(&mut writer)?;
+        full_rounds.serialize(&mut writer)?;
+        partial_rounds.serialize(&mut writer)?;
+        alpha.serialize(&mut writer)?;
+        mds.serialize(&mut writer)?;
+        ark.serialize(&mut writer)?;
+        self.domain_tag.serialize(&mut writer)?;
+        self.bind_key.serialize(&mut writer)?;
+        self.mask_derivation.serialize(&mut writer)?;
+        self.generator.serialize(&mut writer)
+    }
+
+    fn serialized_size(&self) -> usize {
+        let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(&self.sponge);
+        (self.n as u64).serialized_size()
+            + full_rounds.serialized_size()
+            + partial_rounds.serialized_size()
+            + alpha.serialized_size()
+            + mds.serialized_size()
+            + ark.serialized_size()
+            + self.domain_tag.serialized_size()
+            + self.bind_key.serialized_size()
+            + self.mask_derivation.serialized_size()
+            + self.generator.serialized_size()
+    }
+}
+
+impl<C: ProjectiveCurve> CanonicalDeserialize for Parameters<C>
+where
+    C::BaseField: PrimeField,
+{
+    fn deserialize<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let n = u64::deserialize(&mut reader)? as usize;
+        let full_rounds = u32::deserialize(&mut reader)?;
+        let partial_rounds = u32::deserialize(&mut reader)?;
+        let alpha = u64::deserialize(&mut reader)?;
+        let mds = Vec::<Vec<C::BaseField>>::deserialize(&mut reader)?;
+        let ark = Vec::<Vec<C::BaseField>>::deserialize(&mut reader)?;
+        let domain_tag = Option::<C::BaseField>::deserialize(&mut reader)?;
+        let bind_key = bool::deserialize(&mut reader)?;
+        let mask_derivation = MaskDerivation::deserialize(&mut reader)?;
+        // compressed points are checked to be in the prime-order subgroup on deserialization
+        let generator = C::deserialize(&mut reader)?;
+        if generator.is_zero() {
+            return Err(SerializationError::InvalidData);
+        }
+
+        // `PoseidonParameters::new` panics on a malformed shape
+        let width = POSEIDON_RATE + 1;
+        let well_formed = mds.len() == width
+            && mds.iter().all(|row| row.len() == width)
+            && ark.len() as u64 == u64::from(full_rounds) + u64::from(partial_rounds)
+            && ark.iter().all(|row| row.len() == width);
+        if !well_formed {
+            return Err(SerializationError::InvalidData);
         }
+
+        Ok(Self {
+            n,
+            sponge: PoseidonParameters::new(full_rounds, partial_rounds, alpha, mds, ark),
+            domain_tag,
+            bind_key,
+            mask_derivation,
+            generator,
+        })
     }
 }
 
 pub type PublicKey<C> = C;
 
-pub type SecretKey<C: ProjectiveCurve> = C::ScalarField;
+// whether p lies on the curve and in its prime-order subgroup
+pub(crate) fn in_prime_order_subgroup<C: ProjectiveCurve>(p: &C) -> bool {
+    // compressed deserialization recovers the point from its x-coordinate only,
+    // so a point that is off the curve won't come back unchanged
+    let mut bytes = Vec::new();
+    p.serialize(&mut bytes)
+        .expect("serializing into a vector can't fail");
+    let on_curve = C::deserialize(&bytes[..]).is_ok_and(|q| q == *p);
+
+    on_curve && p.mul(C::ScalarField::characteristic()).is_zero()
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretKey<C: ProjectiveCurve>(pub C::ScalarField);
+
+impl<C: ProjectiveCurve> Deref for SecretKey<C> {
+    type Target = C::ScalarField;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<C: ProjectiveCurve> AsRef<C::ScalarField> for SecretKey<C> {
+    fn as_ref(&self) -> &C::ScalarField {
+        &self.0
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: ProjectiveCurve> Zeroize for SecretKey<C> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+// clears the scalar's limbs so that the key doesn't linger in memory after use
+#[cfg(feature = "zeroize")]
+impl<C: ProjectiveCurve> Drop for SecretKey<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
 
+#[derive(Clone, PartialEq, Eq)]
 pub struct Randomness<C: ProjectiveCurve>(pub C::ScalarField);
 
+// the randomness is as sensitive as the plaintext it masks, so it's kept out of logs
+impl<C: ProjectiveCurve> Debug for Randomness<C> {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
+        f.write_str("Randomness(<redacted>)")
+    }
+}
+
+impl<C: ProjectiveCurve> Randomness<C> {
+    /// Wraps a given scalar, e.g. an externally sampled nonce or the one of a test vector.
+    pub fn from_scalar(s: C::ScalarField) -> Self {
+        Self(s)
+    }
+
+    pub fn as_scalar(&self) -> &C::ScalarField {
+        &self.0
+    }
+}
+
 impl<C: ProjectiveCurve> UniformRand for Randomness<C> {
+    /// Samples a non-zero scalar, as `r = 0` would leave the message masked by a public value.
     #[inline]
     fn rand<R: Rng + ?Sized>(rng: &mut R) -> Self {
-        Randomness(<C as ProjectiveCurve>::ScalarField::rand(rng))
+        loop {
+            let r = <C as ProjectiveCurve>::ScalarField::rand(rng);
+            if !r.is_zero() {
+                return Randomness(r);
+            }
+        }
+    }
+}
+
+pub type Plaintext<C> = Vec<<C as ProjectiveCurve>::BaseField>;
+
+pub type Ciphertext<C> = (C, Vec<<C as ProjectiveCurve>::BaseField>);
+
+/// Borrowed [`Ciphertext`] with its ElGamal parts named: `c1 = r * generator` and the masked
+/// slots `c2_i = m_i + dh_i`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CiphertextView<'a, C: ProjectiveCurve> {
+    c1: &'a C,
+    c2: &'a [C::BaseField],
+}
+
+impl<'a, C: ProjectiveCurve> CiphertextView<'a, C> {
+    pub fn c1(&self) -> &'a C {
+        self.c1
+    }
+
+    pub fn c2(&self) -> &'a [C::BaseField] {
+        self.c2
+    }
+
+    /// The `i`-th masked slot, if the ciphertext has that many.
+    pub fn c2_at(&self, i: usize) -> Option<&'a C::BaseField> {
+        self.c2.get(i)
+    }
+
+    /// See [`is_well_formed`].
+    pub fn is_well_formed(&self) -> bool {
+        !self.c1.is_zero() && in_prime_order_subgroup(self.c1)
+    }
+}
+
+impl<'a, C: ProjectiveCurve> From<&'a Ciphertext<C>> for CiphertextView<'a, C> {
+    fn from(cipher: &'a Ciphertext<C>) -> Self {
+        Self {
+            c1: &cipher.0,
+            c2: &cipher.1,
+        }
+    }
+}
+
+impl<C: ProjectiveCurve> From<CiphertextView<'_, C>> for Ciphertext<C> {
+    fn from(view: CiphertextView<'_, C>) -> Self {
+        (*view.c1, view.c2.to_vec())
     }
 }
 
-pub type Plaintext<C: ProjectiveCurve> = Vec<C::BaseField>;
+/// Compares two ciphertexts by the affine form of `c1` and the canonical representation of
+/// every `c2` element, so projective points with different `z` coordinates compare equal.
+pub fn ciphertext_eq<C: ProjectiveCurve>(a: &Ciphertext<C>, b: &Ciphertext<C>) -> bool
+where
+    C::BaseField: PrimeField,
+{
+    a.0.into_affine() == b.0.into_affine()
+        && a.1.len() == b.1.len()
+        && a.1
+            .iter()
+            .zip(&b.1)
+            .all(|(x, y)| x.into_repr() == y.into_repr())
+}
+
+/// Checks that `c1` is a non-zero point of the prime-order subgroup, as every honestly created
+/// ciphertext has. Points of small order on curves with a cofactor, such as JubJub, would
+/// otherwise confine the shared secret `sk * c1` to a handful of values.
+pub fn is_well_formed<C: ProjectiveCurve>(cipher: &Ciphertext<C>) -> bool {
+    !cipher.0.is_zero() && in_prime_order_subgroup(&cipher.0)
+}
+
+/// Ciphertext to several recipients sharing `c1`, with one `c2` vector per recipient.
+pub type MultiCiphertext<C> = (C, Vec<Vec<<C as ProjectiveCurve>::BaseField>>);
+
+/// [`Ciphertext`] with `c1` in affine coordinates, as usually carried by wire formats.
+pub type AffineCiphertext<C> = (
+    <C as ProjectiveCurve>::Affine,
+    Vec<<C as ProjectiveCurve>::BaseField>,
+);
+
+type CiphertextVar<C, CV> = (CV, Vec<FpVar<<C as ProjectiveCurve>::BaseField>>);
+
+/// Size of the constraint system synthesized by [`EncryptCircuit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ConstraintMetrics {
+    pub num_constraints: usize,
+    pub num_witness_variables: usize,
+    pub num_instance_variables: usize,
+}
+
+/// Constraints contributed by each component of [`EncryptCircuit`], in synthesis order, e.g.
+/// `("shared_secret", _)` for the scalar multiplication `r * pk`.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintBreakdown {
+    pub components: Vec<(&'static str, usize)>,
+    pub num_constraints: usize,
+}
+
+// tallies the constraints added since the previous component was recorded
+pub(crate) struct ComponentCounter<F: PrimeField> {
+    cs: ConstraintSystemRef<F>,
+    recorded: usize,
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    components: Vec<(&'static str, usize)>,
+}
+
+impl<F: PrimeField> ComponentCounter<F> {
+    pub(crate) fn new(cs: ConstraintSystemRef<F>) -> Self {
+        Self {
+            recorded: cs.num_constraints(),
+            cs,
+            components: Vec::new(),
+        }
+    }
 
-pub type Ciphertext<C: ProjectiveCurve> = (C, Vec<C::BaseField>);
+    pub(crate) fn record(&mut self, component: &'static str) {
+        let num_constraints = self.cs.num_constraints();
+        self.components
+            .push((component, num_constraints - self.recorded));
+        self.recorded = num_constraints;
+    }
+}
 
-impl<C, CV> EncryptCircuit<C, CV>
+impl<C, CV, S> EncryptCircuit<C, CV, S>
 where
     C: ProjectiveCurve,
     C::BaseField: PrimeField,
     C::Affine: Absorb,
     C::BaseField: Absorb,
     CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
 {
     pub fn new<R: Rng>(
         pk: PublicKey<C>,
         msg: Plaintext<C>,
-        params: Parameters<C>,
+        params: Parameters<C, S>,
+        rnd: &mut R,
+    ) -> Result<Self, EncryptionError> {
+        Self::new_with_aad(pk, msg, Vec::new(), params, rnd)
+    }
+
+    /// Same as [`EncryptCircuit::new`] for a randomness source behind a trait object, e.g. one
+    /// picked at runtime.
+    pub fn new_dyn(
+        pk: PublicKey<C>,
+        msg: Plaintext<C>,
+        params: Parameters<C, S>,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<Self, EncryptionError> {
+        Self::new(pk, msg, params, &mut rng)
+    }
+
+    /// Encrypts `msg` under randomness drawn from a randomness source behind a trait object.
+    pub fn encrypt_dyn(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+        mut rng: &mut dyn RngCore,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        Self::encrypt(pk, msg, &Randomness::rand(&mut rng), params)
+    }
+
+    /// Same as [`EncryptCircuit::new`] but binds the ciphertext to `aad`, which the circuit
+    /// takes as public input after the ciphertext, see [`EncryptCircuit::encrypt_with_aad`].
+    pub fn new_with_aad<R: Rng>(
+        pk: PublicKey<C>,
+        msg: Plaintext<C>,
+        aad: Vec<C::BaseField>,
+        params: Parameters<C, S>,
         rnd: &mut R,
-    ) -> anyhow::Result<Self> {
+    ) -> Result<Self, EncryptionError> {
+        if msg.len() > params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: msg.len(),
+            });
+        }
+
         let r = Randomness::rand(rnd);
 
-        let enc = Self::encrypt(&pk, &msg, &r, &params)
-            .map_err(|e| anyhow!("error encrypting message: {e}"))?;
+        let enc = Self::encrypt_with_aad(&pk, &msg, &aad, &r, &params)?;
 
         Ok(Self {
             r,
             msg,
             pk,
+            aad,
             resulted_ciphertext: enc,
             params,
+            length_prefixed: false,
+            pk_is_public: false,
+            plaintext_is_public: false,
+            range_bits: None,
             _curve_var: PhantomData,
         })
     }
 
+    /// Same as [`EncryptCircuit::new`] but encrypts `msg` in the length-prefixed layout of
+    /// [`EncryptCircuit::encrypt_padded`], which the circuit then enforces on the plaintext.
+    pub fn new_padded<R: Rng>(
+        pk: PublicKey<C>,
+        msg: Plaintext<C>,
+        params: Parameters<C, S>,
+        rnd: &mut R,
+    ) -> Result<Self, EncryptionError> {
+        let padded = Self::pad(&msg, &params)?;
+        let mut circuit = Self::new(pk, padded, params, rnd)?;
+        circuit.length_prefixed = true;
+        Ok(circuit)
+    }
+
+    /// Makes the circuit take the recipient's public key as public input ahead of the
+    /// ciphertext, proving encryption to that specific key rather than to some hidden one,
+    /// see [`EncryptCircuit::get_public_inputs_with_pk`].
+    pub fn with_public_pk(mut self) -> Self {
+        self.pk_is_public = true;
+        self
+    }
+
+    /// Makes the circuit take the plaintext, padded with zeros up to `params.n` slots, as public
+    /// input ahead of everything else, proving that the ciphertext encrypts that known message,
+    /// see [`EncryptCircuit::get_public_inputs_with_plaintext`].
+    pub fn with_public_plaintext(mut self) -> Self {
+        self.plaintext_is_public = true;
+        self
+    }
+
+    /// Makes the circuit additionally prove that every plaintext slot lies in `[0, 2^k)`, by
+    /// decomposing it into `k` bits. `k` has to stay below the bit size of the base field so
+    /// that the decomposition can't wrap around the modulus.
+    pub fn with_range_check(mut self, k: usize) -> Result<Self, EncryptionError> {
+        let capacity = C::BaseField::size_in_bits() - 1;
+        if k > capacity {
+            return Err(EncryptionError::InvalidParameters(format!(
+                "range of {k} bits exceeds the {capacity} bits a base field element can hold"
+            )));
+        }
+
+        self.range_bits = Some(k);
+        Ok(self)
+    }
+
+    /// The randomness `r` the ciphertext was encrypted under. Like a secret key it recovers the
+    /// plaintext from the ciphertext, so it must not leave the prover.
+    pub fn randomness(&self) -> &Randomness<C> {
+        &self.r
+    }
+
+    pub fn public_key(&self) -> &PublicKey<C> {
+        &self.pk
+    }
+
+    /// The encrypted message, which is as sensitive as [`EncryptCircuit::randomness`].
+    pub fn plaintext(&self) -> &Plaintext<C> {
+        &self.msg
+    }
+
+    pub fn parameters(&self) -> &Parameters<C, S> {
+        &self.params
+    }
+
     pub fn keygen<R: CryptoRng + RngCore>(
-        mut rng: &mut R,
-    ) -> anyhow::Result<(SecretKey<C>, PublicKey<C>)> {
+        rng: &mut R,
+    ) -> Result<(SecretKey<C>, PublicKey<C>), EncryptionError> {
         // get a random element from the scalar field
         let secret_key = C::ScalarField::rand(rng);
 
         // compute secret_key*generator to derive the public key
         let mut public_key = C::prime_subgroup_generator();
-        public_key.mul_assign(secret_key.clone());
+        public_key.mul_assign(secret_key);
+
+        Ok((SecretKey(secret_key), public_key))
+    }
+
+    /// Same as [`EncryptCircuit::keygen`] but derives the public key from the generator of
+    /// `params`.
+    pub fn keygen_with_params<R: CryptoRng + RngCore>(
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<(SecretKey<C>, PublicKey<C>), EncryptionError> {
+        let secret_key = C::ScalarField::rand(rng);
 
-        Ok((secret_key, public_key))
+        let mut public_key = params.generator;
+        public_key.mul_assign(secret_key);
+
+        Ok((SecretKey(secret_key), public_key))
+    }
+
+    /// Checks that `pk = sk * generator` for the prime-subgroup generator, e.g. for a keypair
+    /// loaded from storage.
+    pub fn verify_keypair(sk: &SecretKey<C>, pk: &PublicKey<C>) -> bool {
+        let mut expected = C::prime_subgroup_generator();
+        expected.mul_assign(sk.0);
+        expected == *pk
+    }
+
+    /// Same as [`EncryptCircuit::verify_keypair`] but checks `pk` against the generator of
+    /// `params`, for keypairs from [`EncryptCircuit::keygen_with_params`].
+    pub fn verify_keypair_with_params(
+        sk: &SecretKey<C>,
+        pk: &PublicKey<C>,
+        params: &Parameters<C, S>,
+    ) -> bool {
+        let mut expected = params.generator;
+        expected.mul_assign(sk.0);
+        expected == *pk
+    }
+
+    /// Deterministically derives a keypair by expanding `seed` with ChaCha20.
+    pub fn keygen_from_seed(
+        seed: &[u8; 32],
+    ) -> Result<(SecretKey<C>, PublicKey<C>), EncryptionError> {
+        Self::keygen(&mut ChaCha20Rng::from_seed(*seed))
     }
 
+    /// Same as [`EncryptCircuit::keygen_from_seed`] but derives the public key from the
+    /// generator of `params`.
+    pub fn keygen_from_seed_with_params(
+        seed: &[u8; 32],
+        params: &Parameters<C, S>,
+    ) -> Result<(SecretKey<C>, PublicKey<C>), EncryptionError> {
+        Self::keygen_with_params(params, &mut ChaCha20Rng::from_seed(*seed))
+    }
+
+    /// Public inputs of the circuit: `c1`, `c2` padded with zeros up to `params.n` slots, the
+    /// number of slots `c2` actually holds and the domain tag of `params`, if any. Slots beyond
+    /// `params.n` are dropped, see [`EncryptCircuit::try_get_public_inputs`].
     pub fn get_public_inputs<E: PairingEngine>(
         cipher: &Ciphertext<C>,
-        params: &Parameters<C>,
+        params: &Parameters<C, S>,
     ) -> Vec<E::Fr>
     where
         C::BaseField: ToConstraintField<E::Fr>,
@@ -145,171 +962,4534 @@ where
         let c2_inputs = (0..params.n)
             .map(|i| cipher.1.get(i).map_or(C::BaseField::zero(), |&c| c))
             .flat_map(|c2| c2.to_field_elements().unwrap());
-        c1_inputs.into_iter().chain(c2_inputs).collect()
+        let len_inputs = C::BaseField::from(cipher.1.len() as u64)
+            .to_field_elements()
+            .unwrap();
+        let tag_inputs = params
+            .domain_tag
+            .iter()
+            .flat_map(|tag| tag.to_field_elements().unwrap());
+        c1_inputs
+            .into_iter()
+            .chain(c2_inputs)
+            .chain(len_inputs)
+            .chain(tag_inputs)
+            .collect()
     }
 
-    pub fn encrypt(
-        pk: &PublicKey<C>,
-        msg: &Plaintext<C>,
-        r: &Randomness<C>,
-        params: &Parameters<C>,
-    ) -> anyhow::Result<Ciphertext<C>> {
-        let mut c1 = C::prime_subgroup_generator();
-        c1.mul_assign(r.0.clone());
-
-        let mut p_r = pk.clone();
-        p_r.mul_assign(r.0.clone());
-        let p_ra = p_r.into_affine();
-
-        let mut sponge = PoseidonSponge::new(&params.poseidon);
-        sponge.absorb(&p_ra);
-        let dh = sponge.squeeze_field_elements::<C::BaseField>(1).remove(0);
-        let c2 = msg.iter().map(|m| dh.clone() + m).collect();
+    /// Same as [`EncryptCircuit::get_public_inputs`] but fails with `LengthMismatch` on a
+    /// ciphertext of more than `params.n` slots, which no circuit for `params` can prove and
+    /// which would otherwise get truncated.
+    pub fn try_get_public_inputs<E: PairingEngine>(
+        cipher: &Ciphertext<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Vec<E::Fr>, EncryptionError>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        if cipher.1.len() > params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: cipher.1.len(),
+            });
+        }
+        Ok(Self::get_public_inputs::<E>(cipher, params))
+    }
+
+    /// Number of elements returned by [`EncryptCircuit::get_public_inputs`], computed without
+    /// building them.
+    pub fn num_public_inputs<E: PairingEngine>(params: &Parameters<C, S>) -> usize
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let point_len = ToConstraintField::<E::Fr>::to_field_elements(&C::zero())
+            .unwrap()
+            .len();
+        let field_len = ToConstraintField::<E::Fr>::to_field_elements(&C::BaseField::zero())
+            .unwrap()
+            .len();
+        let tag_slots = params.domain_tag.iter().count();
+        point_len + (params.n + 1 + tag_slots) * field_len
+    }
+
+    /// Public inputs of a circuit built with [`EncryptCircuit::new_with_aad`]: the inputs of
+    /// [`EncryptCircuit::get_public_inputs`] followed by the associated data.
+    pub fn get_public_inputs_with_aad<E: PairingEngine>(
+        cipher: &Ciphertext<C>,
+        aad: &[C::BaseField],
+        params: &Parameters<C, S>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let aad_inputs = aad.iter().flat_map(|a| a.to_field_elements().unwrap());
+        Self::get_public_inputs::<E>(cipher, params)
+            .into_iter()
+            .chain(aad_inputs)
+            .collect()
+    }
+
+    /// Public inputs of a circuit made with [`EncryptCircuit::with_public_pk`]: `pk` followed by
+    /// the inputs of [`EncryptCircuit::get_public_inputs`].
+    pub fn get_public_inputs_with_pk<E: PairingEngine>(
+        cipher: &Ciphertext<C>,
+        pk: &PublicKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let pk_inputs = pk.to_field_elements().unwrap();
+        pk_inputs
+            .into_iter()
+            .chain(Self::get_public_inputs::<E>(cipher, params))
+            .collect()
+    }
+
+    /// Public inputs of a circuit made with [`EncryptCircuit::with_public_plaintext`]: `msg`
+    /// padded with zeros up to `params.n` slots followed by the inputs of
+    /// [`EncryptCircuit::get_public_inputs`], or of
+    /// [`EncryptCircuit::get_public_inputs_with_pk`] if the key is public as well.
+    pub fn get_public_inputs_with_plaintext<E: PairingEngine>(
+        cipher: &Ciphertext<C>,
+        msg: &Plaintext<C>,
+        pk: Option<&PublicKey<C>>,
+        params: &Parameters<C, S>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let msg_inputs = (0..params.n)
+            .map(|i| msg.get(i).map_or(C::BaseField::zero(), |&m| m))
+            .flat_map(|m| m.to_field_elements().unwrap());
+        let rest = match pk {
+            Some(pk) => Self::get_public_inputs_with_pk::<E>(cipher, pk, params),
+            None => Self::get_public_inputs::<E>(cipher, params),
+        };
+        msg_inputs.chain(rest).collect()
+    }
+
+    /// Checks that `pk` lies on the curve and in its prime-order subgroup, which rules out
+    /// low-order points that the curve's cofactor would otherwise admit, and that it isn't the
+    /// identity, under which `s = r * pk` is the identity for every `r`.
+    pub fn validate_public_key(pk: &PublicKey<C>) -> Result<(), EncryptionError> {
+        if pk.is_zero() {
+            return Err(EncryptionError::IdentityPoint);
+        }
+        if !in_prime_order_subgroup(pk) {
+            return Err(EncryptionError::PointNotOnCurve);
+        }
+        Ok(())
+    }
+
+    // `r = 0` makes both c1 and s the identity, which reveals that the masks are public
+    pub(crate) fn check_randomness(r: &Randomness<C>) -> Result<(), EncryptionError> {
+        if r.0.is_zero() {
+            return Err(EncryptionError::ZeroRandomness);
+        }
+        Ok(())
+    }
+
+    pub fn encrypt(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        Self::encrypt_with_aad(pk, msg, &[], r, params)
+    }
+
+    /// Same as [`EncryptCircuit::encrypt`] with `c1` returned in affine coordinates, to be
+    /// decrypted with [`EncryptCircuit::decrypt_affine`].
+    pub fn encrypt_affine(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<AffineCiphertext<C>, EncryptionError> {
+        let (c1, c2) = Self::encrypt(pk, msg, r, params)?;
+        Ok((c1.into_affine(), c2))
+    }
+
+    /// Encrypts `msg` under randomness derived with Poseidon from the sender's `sk`, the
+    /// recipient's `pk` and `msg` instead of sampled, so that the same inputs always yield the
+    /// same ciphertext, e.g. for deduplication.
+    ///
+    /// This gives up semantic security: anyone can tell that two ciphertexts from the same
+    /// sender to the same recipient encrypt equal plaintexts. Mixing in `sk` keeps others from
+    /// confirming a guessed plaintext by recomputing the ciphertext.
+    pub fn encrypt_deterministic(
+        pk: &PublicKey<C>,
+        sk: &SecretKey<C>,
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let mut sponge = params.new_sponge();
+        sponge.absorb(&to_bytes![sk.0].unwrap());
+        sponge.absorb(&pk.into_affine());
+        // the length keeps messages that only differ in trailing zeros apart
+        sponge.absorb(&(msg.len() as u64));
+        sponge.absorb(msg);
+        let r = sponge.squeeze_field_elements::<C::ScalarField>(1).remove(0);
+
+        Self::encrypt(pk, msg, &Randomness(r), params)
+    }
+
+    /// Encrypts `msg` with the masks derived from both the shared secret and `aad`, so the
+    /// ciphertext only decrypts correctly under the same associated data.
+    ///
+    /// The associated data isn't part of the ciphertext and has to be supplied again to
+    /// [`EncryptCircuit::decrypt_with_aad`]. An empty `aad` is the same as [`EncryptCircuit::encrypt`].
+    pub fn encrypt_with_aad(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        aad: &[C::BaseField],
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        debug_assert!(
+            msg.len() <= params.n,
+            "a message of {} slots exceeds what circuits of {} slots can prove",
+            msg.len(),
+            params.n
+        );
+        Self::validate_public_key(pk)?;
+        Self::check_randomness(r)?;
+
+        let c1 = Self::commit_c1(r, params);
+
+        let s = Self::shared_secret(pk, &r.0);
+        Ok((c1, Self::mask(&s, Some((*pk, c1)), aad, msg, params)))
+    }
+
+    /// Same as [`EncryptCircuit::encrypt`] but takes ownership of `msg` and zeroizes it before
+    /// returning, whether encryption succeeds or not. Zeroizing a field element overwrites the
+    /// limbs of its Montgomery representation, and the buffer's spare capacity is cleared too.
+    #[cfg(feature = "zeroize")]
+    pub fn encrypt_consuming(
+        pk: &PublicKey<C>,
+        mut msg: Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let cipher = Self::encrypt(pk, &msg, r, params);
+        msg.zeroize();
+        cipher
+    }
+
+    /// Computes `c1 = r * generator` alone, the part of the ciphertext that doesn't depend on
+    /// the message, so that it can be published or committed to before encrypting under the
+    /// same `r`.
+    pub fn commit_c1(r: &Randomness<C>, params: &Parameters<C, S>) -> C {
+        let mut c1 = params.generator;
+        c1.mul_assign(r.0);
+        c1
+    }
+
+    /// Computes the shared point `s = r * pk` that the masks of every slot are derived from, so
+    /// that it can be cached and passed to [`EncryptCircuit::encrypt_with_shared`].
+    ///
+    /// The KDF derives the mask of slot `i` as `H(s, i)`, so it's the point rather than a single
+    /// mask that is shared across slots. Since `s` is tied to `r`, masking several messages with
+    /// the same `s` amounts to reusing `r`: that is the homomorphic case of
+    /// [`EncryptCircuit::add_ciphertexts`], but it also reveals the slot-wise differences of the
+    /// messages to anyone holding both ciphertexts.
+    pub fn compute_shared_secret(
+        pk: &PublicKey<C>,
+        r: &Randomness<C>,
+    ) -> Result<C, EncryptionError> {
+        Self::validate_public_key(pk)?;
+        Self::check_randomness(r)?;
+
+        let mut s = *pk;
+        s.mul_assign(r.0);
+        Ok(s)
+    }
+
+    /// Masks `msg` with the shared point from [`EncryptCircuit::compute_shared_secret`], which
+    /// yields the `c2` part of the ciphertext whose `c1` is `r * generator`.
+    ///
+    /// The shared point alone doesn't determine the recipient, so parameters with
+    /// [`Parameters::bind_key`] set are rejected.
+    pub fn encrypt_with_shared(
+        s: &C,
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Vec<C::BaseField>, EncryptionError> {
+        Self::check_unbound(params)?;
+        Ok(Self::mask(&s.into_affine(), None, &[], msg, params))
+    }
+
+    /// Key encapsulation half of the scheme: samples `r` and returns `c1 = r * generator` along
+    /// with the key `dh` that [`EncryptCircuit::decapsulate`] recovers from `c1`.
+    ///
+    /// `dh` is the mask of the first slot, so `(c1, vec![dh + m])` is the ciphertext that
+    /// [`EncryptCircuit::encrypt`] produces for the single-slot message `m` under the same `r`.
+    pub fn encapsulate<R: Rng>(
+        pk: &PublicKey<C>,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<(C, C::BaseField), EncryptionError> {
+        Self::validate_public_key(pk)?;
+
+        let r = Randomness::rand(rng);
+        let c1 = Self::commit_c1(&r, params);
+
+        let sponge = Self::kdf_sponge(&Self::shared_secret(pk, &r.0), Some((*pk, c1)), &[], params);
+        Ok((c1, Self::slot_masks_of(&sponge, 0..1, params)[0]))
+    }
+
+    /// Recovers the key that [`EncryptCircuit::encapsulate`] returned along with `c1`.
+    pub fn decapsulate(
+        c1: &C,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<C::BaseField, EncryptionError> {
+        let sponge = Self::kdf_sponge(
+            &Self::shared_secret(c1, &sk.0),
+            Self::decryption_binding(c1, sk, params),
+            &[],
+            params,
+        );
+        Ok(Self::slot_masks_of(&sponge, 0..1, params)[0])
+    }
+
+    /// Same as [`EncryptCircuit::encrypt`] but writes `c2` into `out`, replacing its contents,
+    /// and returns `c1`, so that a buffer can be reused across calls.
+    pub fn encrypt_into(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+        out: &mut Vec<C::BaseField>,
+    ) -> Result<C, EncryptionError> {
+        debug_assert!(
+            msg.len() <= params.n,
+            "a message of {} slots exceeds what circuits of {} slots can prove",
+            msg.len(),
+            params.n
+        );
+        Self::validate_public_key(pk)?;
+        Self::check_randomness(r)?;
+
+        let c1 = Self::commit_c1(r, params);
+
+        Self::mask_into(
+            &Self::shared_secret(pk, &r.0),
+            Some((*pk, c1)),
+            &[],
+            msg,
+            params,
+            out,
+        );
+        Ok(c1)
+    }
+
+    /// Same as [`EncryptCircuit::encrypt`] but computes `c1 = r * generator` from a
+    /// precomputed table, which has to be built for the generator of `params`.
+    pub fn encrypt_with_table(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+        generator: &PrecomputedGenerator<C>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        Self::validate_public_key(pk)?;
+        Self::check_randomness(r)?;
+        if *generator.base() != params.generator {
+            return Err(EncryptionError::InvalidParameters(
+                "the table isn't built for the generator of the parameters".into(),
+            ));
+        }
+
+        let c1 = generator.mul(&r.0);
+
+        let s = Self::shared_secret(pk, &r.0);
+        Ok((c1, Self::mask(&s, Some((*pk, c1)), &[], msg, params)))
+    }
+
+    /// Encrypts `msg` followed by its checksum `H(m)`, so that [`EncryptCircuit::decrypt_checked`]
+    /// detects decryption under the wrong key. This takes one slot, leaving room for messages
+    /// of up to `params.n - 1` elements.
+    ///
+    /// The checksum is unkeyed and thus no authentication: anyone who knows the plaintext can
+    /// fix it up after tampering, see [`EncryptCircuit::encrypt_authenticated`] for that.
+    pub fn encrypt_checked(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        if msg.len() >= params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n.saturating_sub(1),
+                got: msg.len(),
+            });
+        }
+
+        let mut checked = Vec::with_capacity(msg.len() + 1);
+        checked.extend_from_slice(msg);
+        checked.push(Self::checksum(msg, params));
+        Self::encrypt(pk, &checked, r, params)
+    }
+
+    /// Encrypts `msg` and appends the authentication tag `H(s, c1, c2)` keyed with the shared
+    /// secret `s` as the last element of `c2`, see [`EncryptCircuit::decrypt_authenticated`].
+    ///
+    /// The tag isn't covered by the circuit, so proofs are only meaningful for the ciphertext
+    /// returned by [`EncryptCircuit::encrypt`].
+    pub fn encrypt_authenticated(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        Self::validate_public_key(pk)?;
+        Self::check_randomness(r)?;
+
+        let c1 = Self::commit_c1(r, params);
+
+        let s = Self::shared_secret(pk, &r.0);
+        let mut c2 = Self::mask(&s, Some((*pk, c1)), &[], msg, params);
+        c2.push(Self::auth_tag(&s, &c1, &c2, params));
+        Ok((c1, c2))
+    }
+
+    /// Encrypts `msg` as `[l, m_1, ..., m_l, 0, ..., 0]`, its length `l` followed by the
+    /// message and zeros up to `params.n` slots, so that [`EncryptCircuit::decrypt_padded`]
+    /// recovers it exactly, trailing zeros included. This takes one slot, leaving room for
+    /// messages of up to `params.n - 1` elements.
+    pub fn encrypt_padded(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        Self::encrypt(pk, &Self::pad(msg, params)?, r, params)
+    }
+
+    /// Decrypts a ciphertext produced by [`EncryptCircuit::encrypt_padded`] and strips the
+    /// length prefix and padding, failing with `InvalidPadding` if they are malformed.
+    pub fn decrypt_padded(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        Self::unpad(Self::decrypt(cipher, sk, params)?)
+    }
+
+    fn pad(
+        msg: &[C::BaseField],
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        if msg.len() >= params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n.saturating_sub(1),
+                got: msg.len(),
+            });
+        }
+
+        let mut padded = Vec::with_capacity(params.n);
+        padded.push(C::BaseField::from(msg.len() as u64));
+        padded.extend_from_slice(msg);
+        padded.resize(params.n, C::BaseField::zero());
+        Ok(padded)
+    }
+
+    fn unpad(mut padded: Plaintext<C>) -> Result<Plaintext<C>, EncryptionError> {
+        let (len, rest) = padded
+            .split_first()
+            .ok_or(EncryptionError::InvalidPadding)?;
+        let len = (0..=rest.len())
+            .find(|&l| C::BaseField::from(l as u64) == *len)
+            .ok_or(EncryptionError::InvalidPadding)?;
+        if rest[len..].iter().any(|m| !m.is_zero()) {
+            return Err(EncryptionError::InvalidPadding);
+        }
+
+        padded.truncate(len + 1);
+        padded.remove(0);
+        Ok(padded)
+    }
+
+    /// Encrypts arbitrary bytes by packing them into field elements, see
+    /// [`EncryptCircuit::pack_bytes`], under fresh randomness. The packed message has to fit
+    /// into `params.n` slots.
+    pub fn encrypt_bytes<R: Rng>(
+        pk: &PublicKey<C>,
+        data: &[u8],
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let packed = Self::pack_bytes(data);
+        if packed.len() > params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: packed.len(),
+            });
+        }
+        Self::encrypt(pk, &packed, &Randomness::rand(rng), params)
+    }
+
+    /// Encrypts `msg` under fresh randomness to the public key given as its compressed affine
+    /// serialization, e.g. as fetched from a key directory. Keys that don't deserialize or
+    /// leave trailing bytes fail with a serialization error, the identity and points outside
+    /// the prime-order subgroup as in [`EncryptCircuit::validate_public_key`].
+    pub fn encrypt_to_bytes<R: Rng>(
+        pk_bytes: &[u8],
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let mut reader = pk_bytes;
+        let pk: C = C::Affine::deserialize(&mut reader)?.into();
+        if !reader.is_empty() {
+            return Err(SerializationError::InvalidData.into());
+        }
+
+        Self::validate_public_key(&pk)?;
+        Self::encrypt(&pk, msg, &Randomness::rand(rng), params)
+    }
+
+    /// Decrypts a ciphertext produced by [`EncryptCircuit::encrypt_bytes`] back into the
+    /// original bytes, failing with `InvalidPadding` if the packing is malformed.
+    pub fn decrypt_bytes(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        Self::unpack_bytes(&Self::decrypt(cipher, sk, params)?)
+    }
+
+    /// Number of bytes packed into each field element, the largest that always stays below the
+    /// modulus, e.g. 31 for the 255-bit base field of JubJub.
+    pub fn bytes_per_element() -> usize {
+        (C::BaseField::size_in_bits() - 1) / 8
+    }
+
+    /// Packs `data` into its length followed by chunks of [`EncryptCircuit::bytes_per_element`]
+    /// bytes read as little-endian integers, the last one padded with zero bytes.
+    pub fn pack_bytes(data: &[u8]) -> Plaintext<C> {
+        let chunks = data
+            .chunks(Self::bytes_per_element())
+            .map(C::BaseField::from_le_bytes_mod_order);
+        ark_std::iter::once(C::BaseField::from(data.len() as u64))
+            .chain(chunks)
+            .collect()
+    }
+
+    /// Inverse of [`EncryptCircuit::pack_bytes`], rejecting a length that doesn't match the
+    /// number of chunks, chunks exceeding their byte width and non-zero padding bytes.
+    pub fn unpack_bytes(packed: &[C::BaseField]) -> Result<Vec<u8>, EncryptionError> {
+        let width = Self::bytes_per_element();
+        let (len, chunks) = packed
+            .split_first()
+            .ok_or(EncryptionError::InvalidPadding)?;
+        // the length has to fill all chunks but the last one
+        let capacity = chunks.len() * width;
+        let len = (capacity.saturating_sub(width - 1)..=capacity)
+            .find(|&l| C::BaseField::from(l as u64) == *len)
+            .ok_or(EncryptionError::InvalidPadding)?;
+
+        let mut data = Vec::with_capacity(capacity);
+        for chunk in chunks {
+            let bytes = chunk.into_repr().to_bytes_le();
+            if bytes[width..].iter().any(|b| *b != 0) {
+                return Err(EncryptionError::InvalidPadding);
+            }
+            data.extend_from_slice(&bytes[..width]);
+        }
+        if data[len..].iter().any(|b| *b != 0) {
+            return Err(EncryptionError::InvalidPadding);
+        }
+
+        data.truncate(len);
+        Ok(data)
+    }
+
+    /// Encrypts signed integers under their centered encoding, see
+    /// [`EncryptCircuit::encode_signed`].
+    pub fn encrypt_signed(
+        pk: &PublicKey<C>,
+        msg: &[i64],
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let encoded = msg.iter().map(|v| Self::encode_signed(*v)).collect();
+        Self::encrypt(pk, &encoded, r, params)
+    }
+
+    /// Decrypts a ciphertext produced by [`EncryptCircuit::encrypt_signed`], failing with
+    /// `ValueOutOfRange` if a slot doesn't decode to an `i64`.
+    pub fn decrypt_signed(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Vec<i64>, EncryptionError> {
+        Self::decrypt(cipher, sk, params)?
+            .iter()
+            .map(Self::decode_signed)
+            .collect()
+    }
+
+    /// Encodes `v` as the field element `v mod p`, so that negative values map to the upper
+    /// half of the field and sums of encodings decode to the sums of the values.
+    pub fn encode_signed(v: i64) -> C::BaseField {
+        let magnitude = C::BaseField::from(v.unsigned_abs());
+        if v < 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Inverse of [`EncryptCircuit::encode_signed`], reading elements above `(p - 1) / 2` as
+    /// the negative values `m - p` and failing with `ValueOutOfRange` outside of `i64`.
+    pub fn decode_signed(m: &C::BaseField) -> Result<i64, EncryptionError> {
+        let negative = m.into_repr() > C::BaseField::modulus_minus_one_div_two();
+        let magnitude = if negative { -*m } else { *m }.into_repr();
+        let (low, high) = magnitude
+            .as_ref()
+            .split_first()
+            .expect("big integers have at least one limb");
+        if high.iter().any(|l| *l != 0) {
+            return Err(EncryptionError::ValueOutOfRange);
+        }
+
+        match negative {
+            // -2^63 is the only magnitude beyond i64::MAX that still fits
+            true if *low <= 1 << 63 => Ok((*low as i64).wrapping_neg()),
+            false if *low <= i64::MAX as u64 => Ok(*low as i64),
+            _ => Err(EncryptionError::ValueOutOfRange),
+        }
+    }
+
+    /// Encrypts `data` of any size under a symmetric key: a random seed is encrypted as a
+    /// single-slot ciphertext and the bytes are XORed with a ChaCha20 keystream keyed by the
+    /// Poseidon hash of that seed.
+    ///
+    /// Only the ciphertext can be proven in-circuit. Neither part is authenticated, so a
+    /// modified blob decrypts to modified bytes rather than failing.
+    pub fn hybrid_encrypt<R: Rng>(
+        pk: &PublicKey<C>,
+        data: &[u8],
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<(Ciphertext<C>, Vec<u8>), EncryptionError> {
+        let seed = C::BaseField::rand(rng);
+        let cipher = Self::encrypt(pk, &vec![seed], &Randomness::rand(rng), params)?;
+
+        let mut blob = data.to_vec();
+        Self::apply_keystream(&seed, &mut blob, params);
+        Ok((cipher, blob))
+    }
+
+    /// Decrypts the output of [`EncryptCircuit::hybrid_encrypt`], failing with `LengthMismatch`
+    /// if the ciphertext doesn't hold exactly the seed.
+    pub fn hybrid_decrypt(
+        cipher: Ciphertext<C>,
+        blob: &[u8],
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        if cipher.1.len() != 1 {
+            return Err(EncryptionError::LengthMismatch {
+                expected: 1,
+                got: cipher.1.len(),
+            });
+        }
+        let seed = Self::decrypt(cipher, sk, params)?[0];
+
+        let mut data = blob.to_vec();
+        Self::apply_keystream(&seed, &mut data, params);
+        Ok(data)
+    }
+
+    // XORs the ChaCha20 keystream keyed by H(seed) into data, which is its own inverse
+    fn apply_keystream(seed: &C::BaseField, data: &mut [u8], params: &Parameters<C, S>) {
+        let mut sponge = params.new_sponge();
+        sponge.absorb(seed);
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&sponge.squeeze_bytes(32));
+
+        let mut keystream = vec![0u8; data.len()];
+        ChaCha20Rng::from_seed(key).fill_bytes(&mut keystream);
+        data.iter_mut().zip(keystream).for_each(|(b, k)| *b ^= k);
+    }
+
+    // compute s = scalar*point, i.e. r*pk when encrypting and secret_key*c1 when decrypting
+    pub(crate) fn shared_secret(point: &C, scalar: &C::ScalarField) -> C::Affine {
+        let mut s = *point;
+        s.mul_assign(*scalar);
+        s.into_affine()
+    }
+
+    // Montgomery ladder keeping the invariant r1 = r0 + point, both candidate states are
+    // computed every step and the bit merely indexes between them, which does make the accessed
+    // address depend on the bit
+    fn shared_secret_ct(point: &C, scalar: &C::ScalarField) -> C::Affine {
+        let (mut r0, mut r1) = (C::zero(), *point);
+        for bit in scalar.into_repr().to_bits_be() {
+            let sum = r0 + r1;
+            let states = [(r0.double(), sum), (sum, r1.double())];
+            (r0, r1) = states[usize::from(bit)];
+        }
+        r0.into_affine()
+    }
+
+    // sponge that has absorbed the shared secret s, the recipient's key and c1 if the
+    // parameters bind them, the domain tag and the associated data
+    pub(crate) fn kdf_sponge(
+        s: &C::Affine,
+        binding: Option<(PublicKey<C>, C)>,
+        aad: &[C::BaseField],
+        params: &Parameters<C, S>,
+    ) -> S::Sponge {
+        let mut sponge = params.new_sponge();
+        sponge.absorb(s);
+        if params.bind_key {
+            let (pk, c1) = binding.expect("binding parameters derive masks from pk and c1");
+            sponge.absorb(&pk.into_affine());
+            sponge.absorb(&c1.into_affine());
+        }
+        if let Some(tag) = &params.domain_tag {
+            sponge.absorb(tag);
+        }
+        sponge.absorb(&aad);
+        sponge
+    }
+
+    // (pk, c1) for parameters that bind ciphertexts to their recipient, with pk = sk*generator
+    pub(crate) fn decryption_binding(
+        c1: &C,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Option<(PublicKey<C>, C)> {
+        params.bind_key.then(|| {
+            let mut pk = params.generator;
+            pk.mul_assign(sk.0);
+            (pk, *c1)
+        })
+    }
+
+    fn check_unbound(params: &Parameters<C, S>) -> Result<(), EncryptionError> {
+        if params.bind_key {
+            return Err(EncryptionError::InvalidParameters(
+                "key binding needs the recipient's public key, not only the shared point".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    // ciphertexts of more than `params.n` slots, such as authenticated ones, can't be
+    // re-encrypted: `encrypt` only takes messages that circuits for `params` can prove
+    fn check_slots(
+        cipher: &Ciphertext<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<(), EncryptionError> {
+        if cipher.1.len() > params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: cipher.1.len(),
+            });
+        }
+        Ok(())
+    }
+
+    // compute c2_i = m_i + dh_i
+    fn mask(
+        s: &C::Affine,
+        binding: Option<(PublicKey<C>, C)>,
+        aad: &[C::BaseField],
+        msg: &[C::BaseField],
+        params: &Parameters<C, S>,
+    ) -> Vec<C::BaseField> {
+        let mut c2 = Vec::with_capacity(msg.len());
+        Self::mask_into(s, binding, aad, msg, params, &mut c2);
+        c2
+    }
+
+    fn mask_into(
+        s: &C::Affine,
+        binding: Option<(PublicKey<C>, C)>,
+        aad: &[C::BaseField],
+        msg: &[C::BaseField],
+        params: &Parameters<C, S>,
+        out: &mut Vec<C::BaseField>,
+    ) {
+        let sponge = Self::kdf_sponge(s, binding, aad, params);
+        out.clear();
+        let masks = Self::slot_masks_of(&sponge, 0..msg.len(), params);
+        out.extend(msg.iter().zip(masks).map(|(m, dh)| dh + m));
+    }
+
+    // compute message_i = c2_i - dh_i
+    fn unmask(
+        s: &C::Affine,
+        binding: Option<(PublicKey<C>, C)>,
+        aad: &[C::BaseField],
+        c2: &[C::BaseField],
+        params: &Parameters<C, S>,
+    ) -> Plaintext<C> {
+        let sponge = Self::kdf_sponge(s, binding, aad, params);
+        let masks = Self::slot_masks_of(&sponge, 0..c2.len(), params);
+        c2.iter().zip(masks).map(|(c2i, dh)| *c2i - dh).collect()
+    }
+
+    // checksum = H(m)
+    fn checksum(msg: &[C::BaseField], params: &Parameters<C, S>) -> C::BaseField {
+        let mut sponge = params.new_sponge();
+        sponge.absorb(&msg);
+        sponge.squeeze_field_elements::<C::BaseField>(1).remove(0)
+    }
+
+    // compute tag = H(s, c1, c2)
+    fn auth_tag(
+        s: &C::Affine,
+        c1: &C,
+        c2: &[C::BaseField],
+        params: &Parameters<C, S>,
+    ) -> C::BaseField {
+        let mut sponge = params.new_sponge();
+        sponge.absorb(s);
+        sponge.absorb(&c1.into_affine());
+        sponge.absorb(&c2);
+        sponge.squeeze_field_elements::<C::BaseField>(1).remove(0)
+    }
+
+    /// Encrypts every message under its own fresh randomness, in parallel when the `parallel`
+    /// feature is enabled.
+    pub fn encrypt_batch<R: Rng>(
+        pk: &PublicKey<C>,
+        msgs: &[Plaintext<C>],
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<Vec<Ciphertext<C>>, EncryptionError> {
+        // the rng can't be shared across threads, so all randomness is sampled upfront
+        let randomness = (0..msgs.len())
+            .map(|_| Randomness::rand(rng))
+            .collect::<Vec<_>>();
+
+        cfg_iter!(msgs)
+            .zip(cfg_iter!(randomness))
+            .map(|(msg, r)| Self::encrypt(pk, msg, r, params))
+            .collect()
+    }
+
+    /// Encrypts `msg` to every key in `pks` under a single randomness `r`, so all recipients
+    /// share `c1 = r * g` and each gets its own `c2` masked with `r * pk_i`.
+    ///
+    /// Reusing `r` keeps the result as secure as separate encryptions as long as the keys are
+    /// honestly generated, but it links the ciphertexts to one another: anyone can tell they
+    /// carry the same message, and a key listed twice yields identical `c2` vectors. Use
+    /// [`EncryptCircuit::encrypt_multi_independent`] when that is not acceptable.
+    ///
+    /// Such a ciphertext is proven as a whole by [`crate::MultiEncryptCircuit`].
+    pub fn encrypt_multi<R: Rng>(
+        pks: &[PublicKey<C>],
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<MultiCiphertext<C>, EncryptionError> {
+        let r = Randomness::<C>::rand(rng);
+
+        let c1 = Self::commit_c1(&r, params);
+
+        let c2s = cfg_iter!(pks)
+            .map(|pk| {
+                Self::validate_public_key(pk)?;
+                let s = Self::shared_secret(pk, &r.0);
+                Ok(Self::mask(&s, Some((*pk, c1)), &[], msg, params))
+            })
+            .collect::<Result<_, EncryptionError>>()?;
+        Ok((c1, c2s))
+    }
+
+    /// Encrypts `msg` to every key in `pks` under independent randomness, producing one full
+    /// ciphertext per recipient, see [`EncryptCircuit::encrypt_multi`].
+    pub fn encrypt_multi_independent<R: Rng>(
+        pks: &[PublicKey<C>],
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<Vec<Ciphertext<C>>, EncryptionError> {
+        let randomness = (0..pks.len())
+            .map(|_| Randomness::rand(rng))
+            .collect::<Vec<_>>();
+
+        cfg_iter!(pks)
+            .zip(cfg_iter!(randomness))
+            .map(|(pk, r)| Self::encrypt(pk, msg, r, params))
+            .collect()
+    }
+
+    /// Adds two ciphertexts slot-wise, producing an encryption of `m_a + m_b`.
+    ///
+    /// Both ciphertexts must have been produced for `pk` under the same randomness `r`, so that
+    /// they share the masks `dh_i` and their sum `2 * dh_i + m_a_i + m_b_i` only needs one mask
+    /// removed. Since that takes `r`, this is meant for the party that produced both ciphertexts.
+    /// Ciphertexts of more than `params.n` slots fail with `LengthMismatch`.
+    pub fn add_ciphertexts(
+        a: &Ciphertext<C>,
+        b: &Ciphertext<C>,
+        pk: &PublicKey<C>,
+        r: &Randomness<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        if a.1.len() != b.1.len() {
+            return Err(EncryptionError::LengthMismatch {
+                expected: a.1.len(),
+                got: b.1.len(),
+            });
+        }
+        Self::check_slots(a, params)?;
+
+        // encrypting zeros under the same randomness yields the masks themselves
+        let (c1, masks) = Self::encrypt(pk, &vec![C::BaseField::zero(); a.1.len()], r, params)?;
+        if a.0 != c1 || b.0 != c1 {
+            return Err(EncryptionError::RandomnessMismatch);
+        }
+
+        let c2 =
+            a.1.iter()
+                .zip(b.1.iter())
+                .zip(masks)
+                .map(|((ca, cb), dh)| *ca + cb - dh)
+                .collect();
         Ok((c1, c2))
     }
 
-    pub fn decrypt(
-        cipher: Ciphertext<C>,
-        sk: SecretKey<C>,
-        params: &Parameters<C>,
-    ) -> anyhow::Result<Plaintext<C>> {
-        let c1 = cipher.0;
-        let c2 = cipher.1;
+    /// Re-encrypts ciphertexts under `old_sk` to `new_pk`, each under fresh randomness and with
+    /// as many slots as before, so padded ciphertexts stay padded.
+    ///
+    /// The tag of an authenticated ciphertext is keyed with the old shared secret and can't be
+    /// carried over, so ciphertexts of more than `params.n` slots fail with `LengthMismatch`.
+    pub fn rotate_key<R: Rng>(
+        old_sk: &SecretKey<C>,
+        new_pk: &PublicKey<C>,
+        ciphertexts: &[Ciphertext<C>],
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<Vec<Ciphertext<C>>, EncryptionError> {
+        let msgs = ciphertexts
+            .iter()
+            .map(|cipher| {
+                Self::check_slots(cipher, params)?;
+                Self::decrypt(cipher.clone(), old_sk, params)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::encrypt_batch(new_pk, &msgs, params, rng)
+    }
+
+    /// Refreshes `cipher` into an unlinkable ciphertext of the same plaintext.
+    ///
+    /// Unlike textbook ElGamal this can't be done with the public key alone: adding `r' * g`
+    /// to `c1` changes the shared secret to `s + r' * pk`, and the Poseidon-derived masks of
+    /// the new secret bear no relation to the old ones. The ciphertext is therefore unmasked
+    /// with `sk` and masked anew under fresh randomness, which like [`EncryptCircuit::rotate_key`]
+    /// fails with `LengthMismatch` on ciphertexts of more than `params.n` slots.
+    pub fn rerandomize<R: Rng>(
+        cipher: &Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        Self::check_slots(cipher, params)?;
+        let msg = Self::decrypt(cipher.clone(), sk, params)?;
+
+        let mut pk = params.generator;
+        pk.mul_assign(sk.0);
+
+        Self::encrypt(&pk, &msg, &Randomness::rand(rng), params)
+    }
+
+    /// Decrypts every slot of `cipher`, so slots padded up to `params.n` come back as well,
+    /// see [`EncryptCircuit::decrypt_trimmed`] for recovering the message at its original length
+    /// and [`EncryptCircuit::decrypt_padded`] for ciphertexts that carry it.
+    pub fn decrypt(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        Self::decrypt_with_aad(cipher, &[], sk, params)
+    }
+
+    /// Same as [`EncryptCircuit::decrypt`] for a ciphertext whose `c1` is given in affine
+    /// coordinates.
+    pub fn decrypt_affine(
+        c1: C::Affine,
+        c2: &[C::BaseField],
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        Self::decrypt((c1.into(), c2.to_vec()), sk, params)
+    }
+
+    /// Lazily decrypts the ciphertexts of `iter`, each one on its own call to `next`.
+    pub fn decrypt_iter<'a, I>(
+        iter: I,
+        sk: SecretKey<C>,
+        params: &'a Parameters<C, S>,
+    ) -> impl Iterator<Item = Result<Plaintext<C>, EncryptionError>> + 'a
+    where
+        I: Iterator<Item = Ciphertext<C>> + 'a,
+        CV: 'a,
+    {
+        iter.map(move |cipher| Self::decrypt(cipher, &sk, params))
+    }
+
+    /// Returns the raw Diffie-Hellman output `s = sk * c1` of `cipher`, for protocols that
+    /// derive their own keys from it instead of the built-in Poseidon KDF.
+    ///
+    /// `s` unmasks every slot of the ciphertext, so it has to be protected like the plaintext
+    /// itself, see [`EncryptCircuit::decrypt_with_shared_point`] for using it with this crate.
+    pub fn recover_shared_point(cipher: &Ciphertext<C>, sk: &SecretKey<C>) -> C::Affine {
+        Self::shared_secret(&cipher.0, &sk.0)
+    }
+
+    /// Decrypts `cipher` given the shared point `s = sk * c1` instead of the secret key, e.g.
+    /// one combined from partial decryptions, see [`crate::threshold::combine_partials`].
+    pub fn decrypt_with_shared_point(
+        cipher: Ciphertext<C>,
+        s: &C,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        Self::check_unbound(params)?;
+        Ok(Self::unmask(&s.into_affine(), None, &[], &cipher.1, params))
+    }
+
+    /// Decrypts a ciphertext produced by [`EncryptCircuit::encrypt_with_aad`]. Associated data
+    /// other than the one used for encryption yields different masks and thus a wrong plaintext.
+    pub fn decrypt_with_aad(
+        cipher: Ciphertext<C>,
+        aad: &[C::BaseField],
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        let s = Self::shared_secret(&cipher.0, &sk.0);
+        let binding = Self::decryption_binding(&cipher.0, sk, params);
+        Ok(Self::unmask(&s, binding, aad, &cipher.1, params))
+    }
+
+    /// Returns the index of the first slot of `cipher` that decrypts to `target`, deriving the
+    /// masks one at a time instead of unmasking the whole ciphertext, so a scan can stop at the
+    /// first match.
+    pub fn find_slot(
+        cipher: &Ciphertext<C>,
+        sk: &SecretKey<C>,
+        target: &C::BaseField,
+        params: &Parameters<C, S>,
+    ) -> Result<Option<usize>, EncryptionError> {
+        let s = Self::shared_secret(&cipher.0, &sk.0);
+        let binding = Self::decryption_binding(&cipher.0, sk, params);
+        let mut sponge = Self::kdf_sponge(&s, binding, &[], params);
+
+        Ok(cipher.1.iter().enumerate().position(|(i, c2i)| {
+            let dh = match params.mask_derivation {
+                MaskDerivation::Indexed | MaskDerivation::Chunked(_) => {
+                    Self::slot_masks_of(&sponge, i..i + 1, params)[0]
+                }
+                // successive squeezes continue the output stream of the sponge
+                MaskDerivation::Squeezed => sponge.squeeze_field_elements::<C::BaseField>(1)[0],
+            };
+            *c2i - dh == *target
+        }))
+    }
+
+    /// Same as [`EncryptCircuit::decrypt`] but derives the shared secret with a fixed sequence
+    /// of group operations, independent of the value of `sk`.
+    ///
+    /// The scalar multiplication behind `mul_assign` in arkworks 0.3 is a double-and-add that
+    /// skips the leading zero bits of the scalar and only adds on set bits, so its timing leaks
+    /// the bit length and Hamming weight of `sk`. Here a Montgomery ladder runs over every bit
+    /// of the scalar's representation and picks its next state without branching. This is not
+    /// constant-time at the memory-access level: the next state is read from a two-entry array
+    /// indexed by the secret bit, since a generic curve doesn't expose the coordinates that a
+    /// masked conditional swap would need. The field arithmetic underneath isn't guaranteed to
+    /// be constant-time by arkworks either, so this is a hardening of the group layer against
+    /// timing rather than a full side-channel guarantee.
+    pub fn decrypt_ct(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        let s = Self::shared_secret_ct(&cipher.0, &sk.0);
+        let binding = params.bind_key.then(|| {
+            let pk = Self::shared_secret_ct(&params.generator, &sk.0);
+            (C::from(pk), cipher.0)
+        });
+        Ok(Self::unmask(&s, binding, &[], &cipher.1, params))
+    }
+
+    /// Decrypts a ciphertext produced by [`EncryptCircuit::encrypt_checked`] and strips the
+    /// checksum, failing with `ChecksumMismatch` if it doesn't match, most likely because `sk`
+    /// isn't the key the ciphertext was encrypted to.
+    pub fn decrypt_checked(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        let mut msg = Self::decrypt(cipher, sk, params)?;
+        let checksum = msg.pop().ok_or(EncryptionError::LengthMismatch {
+            expected: 1,
+            got: 0,
+        })?;
+        if Self::checksum(&msg, params) != checksum {
+            return Err(EncryptionError::ChecksumMismatch);
+        }
+        Ok(msg)
+    }
+
+    /// Verifies the tag appended by [`EncryptCircuit::encrypt_authenticated`] in constant time
+    /// and decrypts the remaining slots, failing with `AuthenticationFailed` on tampering.
+    pub fn decrypt_authenticated(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        let (c1, mut c2) = cipher;
+        let tag = c2.pop().ok_or(EncryptionError::LengthMismatch {
+            expected: 1,
+            got: 0,
+        })?;
+
+        let s = Self::shared_secret(&c1, &sk.0);
+        let binding = Self::decryption_binding(&c1, sk, params);
+        let expected_tag = Self::auth_tag(&s, &c1, &c2, params);
+        let tag_matches = expected_tag
+            .into_repr()
+            .to_bytes_le()
+            .ct_eq(&tag.into_repr().to_bytes_le());
+        if !bool::from(tag_matches) {
+            return Err(EncryptionError::AuthenticationFailed);
+        }
+
+        Ok(Self::unmask(&s, binding, &[], &c2, params))
+    }
+
+    /// Decrypts only the first `len` slots of the ciphertext, dropping the zero padding that
+    /// ciphertexts sized for `params.n` carry beyond the original message length.
+    pub fn decrypt_trimmed(
+        cipher: Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+        len: usize,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        if len > cipher.1.len() {
+            return Err(EncryptionError::LengthMismatch {
+                expected: cipher.1.len(),
+                got: len,
+            });
+        }
+
+        let (c1, mut c2) = cipher;
+        c2.truncate(len);
+        Self::decrypt((c1, c2), sk, params)
+    }
+
+    pub fn decrypt_at(
+        cipher: &Ciphertext<C>,
+        idx: usize,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<C::BaseField, EncryptionError> {
+        let c2 = *cipher.1.get(idx).ok_or(EncryptionError::LengthMismatch {
+            expected: cipher.1.len(),
+            got: idx.saturating_add(1),
+        })?;
+
+        let sponge = Self::kdf_sponge(
+            &Self::shared_secret(&cipher.0, &sk.0),
+            Self::decryption_binding(&cipher.0, sk, params),
+            &[],
+            params,
+        );
+
+        // compute message = c2 - dh_idx
+        Ok(c2 - Self::slot_masks_of(&sponge, idx..idx + 1, params)[0])
+    }
+
+    /// Derives the mask `dh_i` of every slot of `cipher` with a single scalar multiplication,
+    /// for decrypting slots one at a time with [`EncryptCircuit::decrypt_at_with_dh`].
+    pub fn slot_masks(
+        cipher: &Ciphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Vec<C::BaseField> {
+        let sponge = Self::kdf_sponge(
+            &Self::shared_secret(&cipher.0, &sk.0),
+            Self::decryption_binding(&cipher.0, sk, params),
+            &[],
+            params,
+        );
+        Self::slot_masks_of(&sponge, 0..cipher.1.len(), params)
+    }
+
+    /// Decrypts slot `idx` given its mask from [`EncryptCircuit::slot_masks`].
+    pub fn decrypt_at_with_dh(
+        cipher: &Ciphertext<C>,
+        idx: usize,
+        dh: &C::BaseField,
+    ) -> Result<C::BaseField, EncryptionError> {
+        let c2 = cipher.1.get(idx).ok_or(EncryptionError::LengthMismatch {
+            expected: cipher.1.len(),
+            got: idx.saturating_add(1),
+        })?;
+        Ok(*c2 - dh)
+    }
+
+    /// Decrypts the slots in `range`, deriving the shared secret once and the masks only for
+    /// the requested slots.
+    pub fn decrypt_range(
+        cipher: &Ciphertext<C>,
+        range: Range<usize>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        if range.end > cipher.1.len() {
+            return Err(EncryptionError::LengthMismatch {
+                expected: cipher.1.len(),
+                got: range.end,
+            });
+        }
+
+        let sponge = Self::kdf_sponge(
+            &Self::shared_secret(&cipher.0, &sk.0),
+            Self::decryption_binding(&cipher.0, sk, params),
+            &[],
+            params,
+        );
+
+        let masks = Self::slot_masks_of(&sponge, range.clone(), params);
+        Ok(cipher.1[range]
+            .iter()
+            .zip(masks)
+            .map(|(c2i, dh)| *c2i - dh)
+            .collect())
+    }
+
+    // compute dh_i for the slots in `slots` off the sponge that has already absorbed s and the
+    // aad, distinct per slot so that equal plaintext slots don't end up as equal ciphertext slots
+    pub(crate) fn slot_masks_of(
+        sponge: &S::Sponge,
+        slots: Range<usize>,
+        params: &Parameters<C, S>,
+    ) -> Vec<C::BaseField> {
+        match params.mask_derivation {
+            MaskDerivation::Indexed => slots
+                .map(|i| {
+                    let mut sponge = sponge.clone();
+                    sponge.absorb(&(i as u64));
+                    sponge.squeeze_field_elements::<C::BaseField>(1).remove(0)
+                })
+                .collect(),
+            MaskDerivation::Squeezed => {
+                let mut masks = sponge
+                    .clone()
+                    .squeeze_field_elements::<C::BaseField>(slots.end);
+                masks.drain(..slots.start);
+                masks
+            }
+            MaskDerivation::Chunked(width) => {
+                let width = width.get();
+                (slots.start / width..slots.end.div_ceil(width))
+                    .flat_map(|chunk| {
+                        let start = chunk * width;
+                        let mut sponge = sponge.clone();
+                        sponge.absorb(&(chunk as u64));
+                        let mut masks = sponge.squeeze_field_elements::<C::BaseField>(
+                            (start + width).min(slots.end) - start,
+                        );
+                        masks.drain(..slots.start.saturating_sub(start));
+                        masks
+                    })
+                    .collect()
+            }
+        }
+    }
+
+    /// Size of the compressed canonical serialization of a ciphertext of `params.n` slots: the
+    /// compressed point `c1` followed by the length-prefixed vector `c2`.
+    pub fn ciphertext_serialized_size(params: &Parameters<C, S>) -> usize {
+        C::zero().serialized_size()
+            + (params.n as u64).serialized_size()
+            + params.n * C::BaseField::zero().serialized_size()
+    }
+
+    /// Serializes `cipher` in the compressed canonical layout measured by
+    /// [`EncryptCircuit::ciphertext_serialized_size`].
+    pub fn ciphertext_to_bytes(cipher: &Ciphertext<C>) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        cipher
+            .serialize(&mut bytes)
+            .expect("serializing into a vector can't fail");
+        bytes
+    }
+
+    /// Parses a ciphertext of at most `params.n` slots produced by
+    /// [`EncryptCircuit::ciphertext_to_bytes`], rejecting points that are off the curve or
+    /// outside its prime-order subgroup, non-canonical field elements and trailing bytes.
+    pub fn ciphertext_from_bytes(
+        bytes: &[u8],
+        params: &Parameters<C, S>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let mut reader = bytes;
+        let c1 = C::deserialize(&mut reader)?;
+        let len = u64::deserialize(&mut reader)?;
+        // checked before reading, so a forged length can't make the slots outgrow `params.n`
+        if len > params.n as u64 {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: len as usize,
+            });
+        }
+        let c2 = (0..len)
+            .map(|_| C::BaseField::deserialize(&mut reader))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if !reader.is_empty() {
+            return Err(SerializationError::InvalidData.into());
+        }
+        Ok((c1, c2))
+    }
+
+    /// Synthesizes a circuit of the shape given by `params` to measure its size.
+    pub fn constraint_metrics(
+        params: &Parameters<C, S>,
+    ) -> Result<ConstraintMetrics, EncryptionError> {
+        let cs = ConstraintSystem::<C::BaseField>::new_ref();
+        Self::blank(params)?.generate_constraints(cs.clone())?;
+
+        Ok(ConstraintMetrics {
+            num_constraints: cs.num_constraints(),
+            num_witness_variables: cs.num_witness_variables(),
+            num_instance_variables: cs.num_instance_variables(),
+        })
+    }
+
+    /// Breaks the constraints of [`EncryptCircuit::constraint_metrics`] down by the component of
+    /// the circuit that adds them.
+    #[cfg(feature = "metrics")]
+    pub fn constraint_breakdown(
+        params: &Parameters<C, S>,
+    ) -> Result<ConstraintBreakdown, EncryptionError>
+    where
+        CV: AllocVar<C, C::BaseField>,
+    {
+        let cs = ConstraintSystem::<C::BaseField>::new_ref();
+        let mut counter = ComponentCounter::new(cs.clone());
+        Self::blank(params)?.synthesize(cs.clone(), &mut counter)?;
+
+        Ok(ConstraintBreakdown {
+            components: counter.components,
+            num_constraints: cs.num_constraints(),
+        })
+    }
+
+    // circuit with placeholder witnesses, which is enough to determine the constraint system shape
+    fn blank(params: &Parameters<C, S>) -> Result<Self, EncryptionError> {
+        let pk = C::prime_subgroup_generator();
+        let msg = vec![C::BaseField::zero(); params.n];
+        let r = Randomness(C::ScalarField::one());
+        let resulted_ciphertext = Self::encrypt(&pk, &msg, &r, params)?;
+
+        Ok(Self {
+            r,
+            msg,
+            pk,
+            aad: Vec::new(),
+            resulted_ciphertext,
+            params: params.clone(),
+            length_prefixed: false,
+            pk_is_public: false,
+            plaintext_is_public: false,
+            range_bits: None,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Runs the Groth16 setup for circuits built with [`EncryptCircuit::new`] under `params`,
+    /// so the keys are reproducible from a seeded `rng`.
+    ///
+    /// Associated data, the length-prefixed layout, a public `pk` or plaintext and range checks
+    /// change the shape of the circuit, in which case the setup has to be run on a circuit built
+    /// the same way instead.
+    pub fn setup<E, R>(
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<(ProvingKey<E>, VerifyingKey<E>), EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        Ok(Groth16::<E>::circuit_specific_setup(
+            Self::blank(params)?,
+            rng,
+        )?)
+    }
+
+    /// Proves the correctness of `self.resulted_ciphertext` with Groth16.
+    pub fn prove<E, R>(&self, pk: &ProvingKey<E>, rng: &mut R) -> Result<Proof<E>, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        Ok(Groth16::<E>::prove(pk, self.clone(), rng)?)
+    }
+
+    /// Encrypts `msg` under fresh randomness and proves the resulting ciphertext in one go,
+    /// with a proving key from [`EncryptCircuit::setup`] for the same `params`.
+    pub fn encrypt_and_prove<E, R>(
+        pk: &PublicKey<C>,
+        msg: &Plaintext<C>,
+        params: &Parameters<C, S>,
+        proving_key: &ProvingKey<E>,
+        rng: &mut R,
+    ) -> Result<(Ciphertext<C>, Proof<E>), EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        let circuit = Self::new(*pk, msg.clone(), params.clone(), rng)?;
+        let cipher = circuit.resulted_ciphertext.clone();
+        let proof = Groth16::<E>::prove(proving_key, circuit, rng)?;
+        Ok((cipher, proof))
+    }
+
+    /// Proves every circuit against the same proving key, in parallel when the `parallel`
+    /// feature is enabled.
+    ///
+    /// Since `rng` can't be shared across threads, a seed is drawn from it upfront for every
+    /// circuit and expanded with ChaCha20 into the randomness of that circuit's proof, so the
+    /// proofs stay independent while `rng` is only ever used from the calling thread.
+    pub fn prove_batch<E, R>(
+        pk: &ProvingKey<E>,
+        circuits: &[Self],
+        rng: &mut R,
+    ) -> Result<Vec<Proof<E>>, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        let seeds = (0..circuits.len())
+            .map(|_| rng.gen::<[u8; 32]>())
+            .collect::<Vec<_>>();
+
+        cfg_iter!(circuits)
+            .zip(cfg_iter!(seeds))
+            .map(|(circuit, seed)| circuit.prove(pk, &mut ChaCha20Rng::from_seed(*seed)))
+            .collect()
+    }
+
+    /// Verifies a proof produced by [`EncryptCircuit::prove`] against the given ciphertext,
+    /// building the public inputs with [`EncryptCircuit::get_public_inputs`].
+    ///
+    /// The circuit is synthesized over the base field of `C`, so the pairing engine has to be
+    /// one whose scalar field it is, e.g. [`crate::Bls12_381`] for [`crate::JubJub`]. With
+    /// `E::Fr = C::BaseField` the `ToConstraintField` bound on the base field holds trivially,
+    /// leaving the one on `C`, which the arkworks twisted Edwards curves implement.
+    pub fn verify<E>(
+        vk: &VerifyingKey<E>,
+        cipher: &Ciphertext<C>,
+        proof: &Proof<E>,
+        params: &Parameters<C, S>,
+    ) -> Result<bool, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let public_inputs = Self::get_public_inputs::<E>(cipher, params);
+        Ok(Groth16::<E>::verify(vk, &public_inputs, proof)?)
+    }
+
+    /// Verifies every `(ciphertext, proof)` pair against `vk`, processing the verifying key
+    /// only once, and returns whether all of them are valid.
+    ///
+    /// This saves the preparation of `vk` per proof but still runs one pairing check each, it
+    /// doesn't aggregate them into a single check.
+    pub fn verify_batch<E>(
+        vk: &VerifyingKey<E>,
+        items: &[(Ciphertext<C>, Proof<E>)],
+        params: &Parameters<C, S>,
+    ) -> Result<bool, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let pvk = Groth16::<E>::process_vk(vk)?;
+        for (cipher, proof) in items {
+            let public_inputs = Self::get_public_inputs::<E>(cipher, params);
+            if !Groth16::<E>::verify_with_processed_vk(&pvk, &public_inputs, proof)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Verifies every `(ciphertext, proof)` pair like [`EncryptCircuit::verify_batch`], with the
+    /// pairing checks spread over the rayon thread pool and the processed verifying key shared
+    /// between them.
+    #[cfg(feature = "parallel")]
+    pub fn verify_batch_parallel<E>(
+        vk: &VerifyingKey<E>,
+        items: &[(Ciphertext<C>, Proof<E>)],
+        params: &Parameters<C, S>,
+    ) -> Result<bool, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let pvk = Groth16::<E>::process_vk(vk)?;
+        items
+            .par_iter()
+            .map(|(cipher, proof)| {
+                let public_inputs = Self::get_public_inputs::<E>(cipher, params);
+                Ok(Groth16::<E>::verify_with_processed_vk(
+                    &pvk,
+                    &public_inputs,
+                    proof,
+                )?)
+            })
+            .try_reduce(|| true, |a, b| Ok(a && b))
+    }
+
+    /// Alias of [`EncryptCircuit::verify`] named after [`EncryptCircuit::get_public_inputs`].
+    pub fn verify_proof<E>(
+        vk: &VerifyingKey<E>,
+        cipher: &Ciphertext<C>,
+        proof: &Proof<E>,
+        params: &Parameters<C, S>,
+    ) -> Result<bool, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        C: ToConstraintField<E::Fr>,
+    {
+        Self::verify(vk, cipher, proof, params)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verify_encryption(
+        &self,
+        cs: ConstraintSystemRef<C::BaseField>,
+        pk: &CV,
+        plaintext: &[FpVar<C::BaseField>],
+        ciphertext: &CiphertextVar<C, CV>,
+        len: &FpVar<C::BaseField>,
+        kdf_inputs: &[FpVar<C::BaseField>],
+        counter: &mut ComponentCounter<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if plaintext.len() > self.params.n || ciphertext.1.len() > self.params.n {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let randomness = Self::scalar_bits_var(ns!(cs, "encryption_randomness"), &self.r.0)?;
+        counter.record("randomness_bits");
+
+        // compute s = randomness*pk, where the complete twisted Edwards addition keeps r = 0 and
+        // pk = identity satisfiable, so those are rejected natively rather than in the circuit
+        let s = pk.clone().scalar_mul_le(randomness.iter())?;
+        counter.record("shared_secret");
+
+        // compute c1 = randomness*generator
+        let c1 = Self::generator_mul_var(&randomness, &self.params.generator)?;
+        counter.record("generator_mul");
+
+        let sponge =
+            Self::kdf_sponge_var(cs.clone(), &s, pk, &ciphertext.0, kdf_inputs, &self.params)?;
+        counter.record("kdf_absorb");
+
+        c1.enforce_equal(&ciphertext.0)?;
+        counter.record("c1_equality");
+
+        // the first `len` slots have to hold the encryption of the plaintext, the padding after
+        // them has to be zero in both the plaintext and the ciphertext
+        let is_message = Self::length_mask(cs.clone(), len, ciphertext.1.len())?;
+        counter.record("length_mask");
+        let masks = Self::slot_masks_var(&sponge, plaintext.len(), &self.params)?;
+        counter.record("slot_masks");
+        plaintext
+            .iter()
+            .zip(masks)
+            .zip(ciphertext.1.iter())
+            .zip(is_message)
+            .try_for_each(|(((m, dh), exp), is_message)| {
+                let c2 = dh + m;
+                c2.conditional_enforce_equal(exp, &is_message)?;
+                exp.conditional_enforce_equal(&FpVar::zero(), &is_message.not())?;
+                m.conditional_enforce_equal(&FpVar::zero(), &is_message.not())
+            })?;
+        counter.record("slot_checks");
+
+        if let Some(k) = self.range_bits {
+            plaintext
+                .iter()
+                .try_for_each(|m| Self::enforce_in_range(cs.clone(), m, k))?;
+            counter.record("range_checks");
+        }
+
+        if self.length_prefixed {
+            let (len, msg) = plaintext
+                .split_first()
+                .ok_or(SynthesisError::Unsatisfiable)?;
+            let is_message = Self::length_mask(cs, len, msg.len())?;
+            msg.iter()
+                .zip(is_message)
+                .try_for_each(|(m, b)| m.conditional_enforce_equal(&FpVar::zero(), &b.not()))?;
+            counter.record("length_prefix");
+        }
+        Ok(())
+    }
+
+    // enforces 0 <= value < 2^k by recomposing it from k witnessed bits, which no
+    // assignment can satisfy for a value outside of the range as long as 2^k < p
+    fn enforce_in_range(
+        cs: ConstraintSystemRef<C::BaseField>,
+        value: &FpVar<C::BaseField>,
+        k: usize,
+    ) -> Result<(), SynthesisError> {
+        let value_bits = value.value().map(|v| v.into_repr().to_bits_le());
+        let bits = (0..k)
+            .map(|i| {
+                Boolean::new_witness(ns!(cs, "range_bit"), || {
+                    value_bits.as_ref().map(|bits| bits[i]).map_err(|e| *e)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Boolean::le_bits_to_fp_var(&bits)?.enforce_equal(value)
+    }
+
+    // booleans b_i = (i < len) for i in 0..n; requiring them to be non-increasing and to sum up
+    // to `len` leaves exactly one satisfying assignment for any len <= n and none otherwise
+    pub(crate) fn length_mask(
+        cs: ConstraintSystemRef<C::BaseField>,
+        len: &FpVar<C::BaseField>,
+        n: usize,
+    ) -> Result<Vec<Boolean<C::BaseField>>, SynthesisError> {
+        let len_value = len.value();
+        let mask = (0..n)
+            .map(|i| {
+                Boolean::new_witness(ns!(cs, "length_mask"), || {
+                    Ok(C::BaseField::from(i as u64) < len_value?)
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for pair in mask.windows(2) {
+            pair[1]
+                .and(&pair[0].not())?
+                .enforce_equal(&Boolean::FALSE)?;
+        }
+        mask.iter()
+            .fold(FpVar::zero(), |sum, b| sum + FpVar::from(b.clone()))
+            .enforce_equal(len)?;
+        Ok(mask)
+    }
+
+    // compute bits*generator from its precomputed power-of-two multiples.
+    // Allocating the generator with `new_constant` costs no constraints and its doublings are
+    // free, but `scalar_mul_le` still spends an addition and a selection per bit, while twisted
+    // Edwards gadgets add the precomputed multiples two bits at a time through lookups, which
+    // saves one constraint per scalar bit on JubJub
+    pub(crate) fn generator_mul_var(
+        bits: &[Boolean<C::BaseField>],
+        generator: &C,
+    ) -> Result<CV, SynthesisError> {
+        let generator_powers = ark_std::iter::successors(Some(*generator), |p| Some(p.double()))
+            .take(bits.len())
+            .collect::<Vec<_>>();
+        let mut result = CV::zero();
+        result.precomputed_base_scalar_mul_le(bits.iter().zip(&generator_powers))?;
+        Ok(result)
+    }
+
+    // flatten a scalar to its little-endian bit vector, allocated as witness
+    pub(crate) fn scalar_bits_var(
+        cs: impl Into<Namespace<C::BaseField>>,
+        scalar: &C::ScalarField,
+    ) -> Result<Vec<Boolean<C::BaseField>>, SynthesisError> {
+        let bytes = to_bytes![scalar].unwrap();
+        Ok(UInt8::new_witness_vec(cs, &bytes)?
+            .iter()
+            .flat_map(|b| b.to_bits_le().unwrap())
+            .collect())
+    }
+
+    // in-circuit counterpart of `kdf_sponge`: the sponge that has absorbed s, then pk and c1 if
+    // the parameters bind them, then `kdf_inputs`, i.e. the domain tag and the associated data
+    pub(crate) fn kdf_sponge_var(
+        cs: ConstraintSystemRef<C::BaseField>,
+        s: &CV,
+        pk: &CV,
+        c1: &CV,
+        kdf_inputs: &[FpVar<C::BaseField>],
+        params: &Parameters<C, S>,
+    ) -> Result<S::SpongeVar, SynthesisError> {
+        let mut sponge = params.new_sponge_var(cs);
+        sponge.absorb(s)?;
+        if params.bind_key {
+            sponge.absorb(pk)?;
+            sponge.absorb(c1)?;
+        }
+        sponge.absorb(&kdf_inputs)?;
+        Ok(sponge)
+    }
+
+    // `kdf_inputs` of circuits that don't take the domain tag as public input: the tag is baked
+    // into the circuit as a constant, which binds the verifying key to it
+    pub(crate) fn constant_kdf_inputs(params: &Parameters<C, S>) -> Vec<FpVar<C::BaseField>> {
+        params.domain_tag.map(FpVar::constant).into_iter().collect()
+    }
+
+    // in-circuit counterpart of `slot_masks_of` for the first n slots
+    pub(crate) fn slot_masks_var(
+        sponge: &S::SpongeVar,
+        n: usize,
+        params: &Parameters<C, S>,
+    ) -> Result<Vec<FpVar<C::BaseField>>, SynthesisError> {
+        match params.mask_derivation {
+            MaskDerivation::Indexed => (0..n)
+                .map(|i| {
+                    let mut sponge = sponge.clone();
+                    sponge.absorb(&FpVar::constant(C::BaseField::from(i as u64)))?;
+                    sponge.squeeze_field_elements(1).map(|r| r[0].clone())
+                })
+                .collect(),
+            MaskDerivation::Squeezed => sponge.clone().squeeze_field_elements(n),
+            MaskDerivation::Chunked(width) => {
+                let width = width.get();
+                let chunks = (0..n.div_ceil(width))
+                    .map(|chunk| {
+                        let mut sponge = sponge.clone();
+                        sponge.absorb(&FpVar::constant(C::BaseField::from(chunk as u64)))?;
+                        sponge.squeeze_field_elements(width.min(n - chunk * width))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(chunks.concat())
+            }
+        }
+    }
+
+    pub(crate) fn ciphertext_var(
+        &self,
+        cs: ConstraintSystemRef<C::BaseField>,
+        mode: AllocationMode,
+    ) -> Result<CiphertextVar<C, CV>, SynthesisError> {
+        let c1 = CV::new_variable(
+            ns!(cs, "ciphertext"),
+            || Ok(self.resulted_ciphertext.0),
+            mode,
+        )?;
+        let c2 = (0..self.params.n)
+            .map(|i| {
+                FpVar::<C::BaseField>::new_variable(
+                    ns!(cs, "ciphertext"),
+                    || {
+                        Ok(self
+                            .resulted_ciphertext
+                            .1
+                            .get(i)
+                            .map_or(C::BaseField::zero(), |c| *c))
+                    },
+                    mode,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok((c1, c2))
+    }
+}
+
+impl<C, CV, S> ConstraintSynthesizer<C::BaseField> for EncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        let mut counter = ComponentCounter::new(cs.clone());
+        self.synthesize(cs, &mut counter)
+    }
+}
+
+impl<C, CV, S> EncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn synthesize(
+        self,
+        cs: ConstraintSystemRef<C::BaseField>,
+        counter: &mut ComponentCounter<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        // the message would otherwise get silently truncated to `n` slots
+        if self.msg.len() > self.params.n {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let msg_mode = if self.plaintext_is_public {
+            AllocationMode::Input
+        } else {
+            AllocationMode::Witness
+        };
+        let message: Vec<_> = (0..self.params.n)
+            .map(|i| {
+                FpVar::<C::BaseField>::new_variable(
+                    ns!(cs, "plaintext"),
+                    || Ok(self.msg.get(i).map_or(C::BaseField::zero(), |c| *c)),
+                    msg_mode,
+                )
+            })
+            .collect::<Result<_, _>>()?;
+        let pk_mode = if self.pk_is_public {
+            AllocationMode::Input
+        } else {
+            AllocationMode::Witness
+        };
+        let pk = CV::new_variable(ns!(cs, "pub_key"), || Ok(self.pk), pk_mode)?;
+        let ciphertext = self.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+        let len = FpVar::<C::BaseField>::new_input(ns!(cs, "ciphertext_length"), || {
+            Ok(C::BaseField::from(self.resulted_ciphertext.1.len() as u64))
+        })?;
+        let domain_tag = self
+            .params
+            .domain_tag
+            .map(|tag| FpVar::new_input(ns!(cs, "domain_tag"), || Ok(tag)))
+            .transpose()?;
+        let aad = Vec::<FpVar<C::BaseField>>::new_input(ns!(cs, "aad"), || Ok(self.aad.clone()))?;
+
+        // the sponge absorbs the domain tag and the associated data after the shared secret
+        let kdf_inputs = domain_tag.into_iter().chain(aad).collect::<Vec<_>>();
+        counter.record("allocation");
+        self.verify_encryption(
+            cs.clone(),
+            &pk,
+            &message,
+            &ciphertext,
+            &len,
+            &kdf_inputs,
+            counter,
+        )
+    }
+}
+
+/// [`EncryptCircuit`] for messages of exactly `N` slots, fixing the width of the circuit at the
+/// type level.
+///
+/// The constraint system and public inputs are those of an [`EncryptCircuit`] for
+/// `params.n == N` encrypting a full message, so keys from [`EncryptCircuit::setup`] work for
+/// both.
+#[derive(Clone)]
+pub struct EncryptCircuitN<C, CV, const N: usize, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    r: Randomness<C>,
+    msg: [C::BaseField; N],
+    pk: PublicKey<C>,
+    pub resulted_ciphertext: (C, [C::BaseField; N]),
+    params: Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, const N: usize, S> EncryptCircuitN<C, CV, N, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    /// Encrypts `msg` under fresh randomness, failing with `LengthMismatch` unless
+    /// `params.n == N`.
+    pub fn new<R: Rng>(
+        pk: PublicKey<C>,
+        msg: [C::BaseField; N],
+        params: Parameters<C, S>,
+        rnd: &mut R,
+    ) -> Result<Self, EncryptionError> {
+        if params.n != N {
+            return Err(EncryptionError::LengthMismatch {
+                expected: N,
+                got: params.n,
+            });
+        }
+
+        let r = Randomness::rand(rnd);
+        let (c1, c2) = EncryptCircuit::<C, CV, S>::encrypt(&pk, &msg.to_vec(), &r, &params)?;
+        let c2 = c2.try_into().expect("a full message masks to N slots");
+
+        Ok(Self {
+            r,
+            msg,
+            pk,
+            resulted_ciphertext: (c1, c2),
+            params,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Public inputs of the circuit, the same as [`EncryptCircuit::get_public_inputs`] yields
+    /// for the dynamic form of `cipher`.
+    pub fn get_public_inputs<E: PairingEngine>(
+        cipher: &(C, [C::BaseField; N]),
+        params: &Parameters<C, S>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        EncryptCircuit::<C, CV, S>::get_public_inputs::<E>(&(cipher.0, cipher.1.to_vec()), params)
+    }
+
+    /// Proves the encryption with a proving key from [`EncryptCircuit::setup`] for `params`.
+    pub fn prove<E, R>(&self, pk: &ProvingKey<E>, rng: &mut R) -> Result<Proof<E>, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        Ok(Groth16::<E>::prove(pk, self.clone(), rng)?)
+    }
+}
+
+impl<C, CV, const N: usize, S> From<EncryptCircuitN<C, CV, N, S>> for EncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn from(circuit: EncryptCircuitN<C, CV, N, S>) -> Self {
+        Self {
+            r: circuit.r,
+            msg: circuit.msg.to_vec(),
+            pk: circuit.pk,
+            aad: Vec::new(),
+            resulted_ciphertext: (
+                circuit.resulted_ciphertext.0,
+                circuit.resulted_ciphertext.1.to_vec(),
+            ),
+            params: circuit.params,
+            length_prefixed: false,
+            pk_is_public: false,
+            plaintext_is_public: false,
+            range_bits: None,
+            _curve_var: PhantomData,
+        }
+    }
+}
+
+impl<C, CV, const N: usize, S> TryFrom<EncryptCircuit<C, CV, S>> for EncryptCircuitN<C, CV, N, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    type Error = EncryptionError;
+
+    /// Fails with `LengthMismatch` unless the message and the ciphertext fill all `N` slots of
+    /// `params.n == N`, and with `InvalidParameters` for circuits whose shape was changed by
+    /// associated data, the length-prefixed layout, a public `pk` or plaintext or range checks.
+    fn try_from(circuit: EncryptCircuit<C, CV, S>) -> Result<Self, Self::Error> {
+        if circuit.length_prefixed
+            || circuit.pk_is_public
+            || circuit.plaintext_is_public
+            || circuit.range_bits.is_some()
+            || !circuit.aad.is_empty()
+        {
+            return Err(EncryptionError::InvalidParameters(
+                "only circuits of the default shape have a fixed-length form".into(),
+            ));
+        }
+
+        let mismatch = |got: usize| EncryptionError::LengthMismatch { expected: N, got };
+        if circuit.params.n != N {
+            return Err(mismatch(circuit.params.n));
+        }
+        let msg = circuit
+            .msg
+            .try_into()
+            .map_err(|msg: Vec<_>| mismatch(msg.len()))?;
+        let (c1, c2) = circuit.resulted_ciphertext;
+        let c2 = c2.try_into().map_err(|c2: Vec<_>| mismatch(c2.len()))?;
+
+        Ok(Self {
+            r: circuit.r,
+            msg,
+            pk: circuit.pk,
+            resulted_ciphertext: (c1, c2),
+            params: circuit.params,
+            _curve_var: PhantomData,
+        })
+    }
+}
+
+impl<C, CV, const N: usize, S> ConstraintSynthesizer<C::BaseField> for EncryptCircuitN<C, CV, N, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if self.params.n != N {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        // same allocation order as `EncryptCircuit`, minus the padding of short messages
+        let message = self
+            .msg
+            .iter()
+            .map(|m| FpVar::<C::BaseField>::new_witness(ns!(cs, "plaintext"), || Ok(*m)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let pk = CV::new_witness(ns!(cs, "pub_key"), || Ok(self.pk))?;
+        let c1 = CV::new_input(ns!(cs, "ciphertext"), || Ok(self.resulted_ciphertext.0))?;
+        let c2 = self
+            .resulted_ciphertext
+            .1
+            .iter()
+            .map(|c| FpVar::<C::BaseField>::new_input(ns!(cs, "ciphertext"), || Ok(*c)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let len = FpVar::<C::BaseField>::new_input(ns!(cs, "ciphertext_length"), || {
+            Ok(C::BaseField::from(N as u64))
+        })?;
+        let domain_tag = self
+            .params
+            .domain_tag
+            .map(|tag| FpVar::new_input(ns!(cs, "domain_tag"), || Ok(tag)))
+            .transpose()?;
+
+        let mut counter = ComponentCounter::new(cs.clone());
+        EncryptCircuit::from(self).verify_encryption(
+            cs.clone(),
+            &pk,
+            &message,
+            &(c1, c2),
+            &len,
+            &domain_tag.into_iter().collect::<Vec<_>>(),
+            &mut counter,
+        )
+    }
+}
+
+// shared with the tests of the other modules, which build on the JubJub instantiation too
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{JubJub, JubJubVar};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_std::sync::atomic::{AtomicUsize, Ordering};
+    use ark_std::sync::Arc;
+    use ark_std::test_rng;
+    use tracing::span;
+
+    pub(crate) type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+
+    // the message `[1, 2, ..., len]`, distinct non-zero slots
+    pub(crate) fn message(len: usize) -> Plaintext<JubJub> {
+        (1..=len as u64).map(Fq::from).collect()
+    }
+
+    #[test]
+    fn equal_slots_encrypt_to_distinct_slots() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let x = Fq::from(42u64);
+
+        let circuit = Circuit::new(pk, vec![x, x], params.clone(), &mut rng).unwrap();
+        let (_, c2) = &circuit.resulted_ciphertext;
+        assert_ne!(c2[0], c2[1]);
+        assert_eq!(
+            Circuit::decrypt(circuit.resulted_ciphertext.clone(), &sk, &params).unwrap(),
+            vec![x, x]
+        );
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn decrypt_trimmed_drops_the_padding_of_short_messages() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(8);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(3u64), Fq::from(1u64), Fq::from(4u64)];
+        let mut padded = msg.clone();
+        padded.resize(params.n, Fq::zero());
+
+        let cipher = Circuit::encrypt(&pk, &padded, &Randomness::rand(&mut rng), &params).unwrap();
+        assert_eq!(
+            Circuit::decrypt_trimmed(cipher.clone(), &sk, &params, 3).unwrap(),
+            msg
+        );
+        assert!(Circuit::decrypt_trimmed(cipher, &sk, &params, 9).is_err());
+    }
+
+    #[test]
+    fn rerandomized_ciphertexts_differ_but_decrypt_identically() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+
+        let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+        let fresh = Circuit::rerandomize(&cipher, &sk, &params, &mut rng).unwrap();
+        assert_ne!(fresh.0, cipher.0);
+        assert!(fresh.1.iter().zip(&cipher.1).all(|(a, b)| a != b));
+        assert_eq!(Circuit::decrypt(fresh.clone(), &sk, &params).unwrap(), msg);
+
+        // the fresh ciphertext is a regular encryption to the key of `sk`
+        let again = Circuit::rerandomize(&fresh, &sk, &params, &mut rng).unwrap();
+        assert_ne!(again, fresh);
+        assert_eq!(Circuit::decrypt(again, &sk, &params).unwrap(), msg);
+    }
+
+    #[test]
+    fn batch_encryption_matches_serial_decryption() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msgs = (0..8u64)
+            .map(|i| vec![Fq::from(i), Fq::from(i * i)])
+            .collect::<Vec<_>>();
+
+        let ciphers = Circuit::encrypt_batch(&pk, &msgs, &params, &mut rng).unwrap();
+        assert_eq!(ciphers.len(), msgs.len());
+        for (cipher, msg) in ciphers.iter().zip(&msgs) {
+            assert_eq!(
+                &Circuit::decrypt(cipher.clone(), &sk, &params).unwrap(),
+                msg
+            );
+        }
+        // every message is encrypted under its own randomness
+        for (i, a) in ciphers.iter().enumerate() {
+            assert!(ciphers[i + 1..].iter().all(|b| a.0 != b.0));
+        }
+
+        assert!(Circuit::encrypt_batch(&pk, &[], &params, &mut rng)
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            Circuit::encrypt_batch(&JubJub::zero(), &msgs, &params, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn constant_time_decryption_matches_decrypt() {
+        use ark_ed_on_bls12_381::Fr;
+
+        let mut rng = test_rng();
+        let g = JubJub::prime_subgroup_generator();
+        for scalar in [Fr::zero(), Fr::one(), -Fr::one(), Fr::rand(&mut rng)] {
+            assert_eq!(
+                Circuit::shared_secret_ct(&g, &scalar),
+                g.mul(scalar.into_repr()).into_affine()
+            );
+        }
+
+        let msg = message(3);
+        for bind_key in [false, true] {
+            let params = Parameters {
+                bind_key,
+                ..Parameters::<JubJub>::default_multi(3)
+            };
+            let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+            let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+            assert_eq!(
+                Circuit::decrypt_ct(cipher.clone(), &sk, &params).unwrap(),
+                Circuit::decrypt(cipher.clone(), &sk, &params).unwrap()
+            );
+            assert_eq!(Circuit::decrypt_ct(cipher, &sk, &params).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn built_parameters_encrypt_and_satisfy_the_circuit() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::builder()
+            .n(3)
+            .poseidon_full_rounds(8)
+            .poseidon_partial_rounds(60)
+            .domain_tag(Fq::from(21u64))
+            .build()
+            .unwrap();
+        assert_eq!(params.n, 3);
+        assert_eq!(poseidon_params_parts(&params.sponge).1, 60);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+
+        let circuit = Circuit::new(pk, msg.clone(), params.clone(), &mut rng).unwrap();
+        assert_eq!(
+            Circuit::decrypt(circuit.resulted_ciphertext.clone(), &sk, &params).unwrap(),
+            msg
+        );
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let invalid = [
+            Parameters::<JubJub>::builder().poseidon_rate(3),
+            Parameters::<JubJub>::builder().poseidon_full_rounds(7),
+            Parameters::<JubJub>::builder().poseidon_partial_rounds(10),
+            Parameters::<JubJub>::builder().poseidon_alpha(2),
+        ];
+        for builder in invalid {
+            assert!(matches!(
+                builder.build(),
+                Err(EncryptionError::InvalidParameters(_))
+            ));
+        }
+        assert!(matches!(
+            Parameters::<JubJub>::builder()
+                .generator(JubJub::zero())
+                .build(),
+            Err(EncryptionError::PointNotOnCurve)
+        ));
+    }
+
+    #[test]
+    fn every_recipient_decrypts_a_multi_recipient_ciphertext() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let keys = (0..3)
+            .map(|_| Circuit::keygen(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let pks = keys.iter().map(|(_, pk)| *pk).collect::<Vec<_>>();
+        let msg = message(2);
+
+        let (c1, c2s) = Circuit::encrypt_multi(&pks, &msg, &params, &mut rng).unwrap();
+        assert_eq!(c2s.len(), pks.len());
+        for ((sk, _), c2) in keys.iter().zip(c2s) {
+            assert_eq!(Circuit::decrypt((c1, c2), sk, &params).unwrap(), msg);
+        }
+
+        let ciphers = Circuit::encrypt_multi_independent(&pks, &msg, &params, &mut rng).unwrap();
+        assert_ne!(ciphers[0].0, ciphers[1].0);
+        for ((sk, _), cipher) in keys.iter().zip(ciphers) {
+            assert_eq!(Circuit::decrypt(cipher, sk, &params).unwrap(), msg);
+        }
+
+        let with_identity = [pks[0], JubJub::zero()];
+        assert!(matches!(
+            Circuit::encrypt_multi(&with_identity, &msg, &params, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        assert!(matches!(
+            Circuit::encrypt_multi_independent(&with_identity, &msg, &params, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn ciphertexts_compare_by_their_canonical_form() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+        assert_eq!(r, Randomness::from_scalar(*r.as_scalar()));
+        assert_ne!(r, Randomness::rand(&mut rng));
+
+        let cipher = Circuit::encrypt(&pk, &message(2), &r, &params).unwrap();
+        // the same point with `z = 1`, which the projective one is unlikely to have
+        let normalized = (cipher.0.into_affine().into(), cipher.1.clone());
+        assert!(ciphertext_eq(&cipher, &normalized));
+
+        let mut other_slot = cipher.clone();
+        other_slot.1[1] += Fq::one();
+        assert!(!ciphertext_eq(&cipher, &other_slot));
+        let mut shorter = cipher.clone();
+        shorter.1.pop();
+        assert!(!ciphertext_eq(&cipher, &shorter));
+        let other_c1 = (cipher.0.double(), cipher.1.clone());
+        assert!(!ciphertext_eq(&cipher, &other_c1));
+    }
+
+    #[test]
+    fn cached_shared_secrets_encrypt_like_encrypt() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+
+        let s = Circuit::compute_shared_secret(&pk, &r).unwrap();
+        for msg in [message(3), message(1)] {
+            let (c1, c2) = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+            assert_eq!(Circuit::encrypt_with_shared(&s, &msg, &params).unwrap(), c2);
+            assert_eq!(c1, Circuit::commit_c1(&r, &params));
+            assert_eq!(
+                Circuit::decrypt_with_shared_point((c1, c2), &s, &params).unwrap(),
+                msg
+            );
+        }
+        assert_eq!(
+            s.into_affine(),
+            Circuit::recover_shared_point(
+                &Circuit::encrypt(&pk, &message(1), &r, &params).unwrap(),
+                &sk
+            )
+        );
+
+        assert!(matches!(
+            Circuit::compute_shared_secret(&JubJub::zero(), &r),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        assert!(matches!(
+            Circuit::compute_shared_secret(&pk, &Randomness::from_scalar(Zero::zero())),
+            Err(EncryptionError::ZeroRandomness)
+        ));
+        let bound = Parameters {
+            bind_key: true,
+            ..params
+        };
+        assert!(matches!(
+            Circuit::encrypt_with_shared(&s, &message(3), &bound),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn bytes_of_any_length_round_trip() {
+        use ark_std::rand::RngCore;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(5);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        assert_eq!(Circuit::bytes_per_element(), 31);
+
+        for len in [0, 1, 30, 31, 32, 62, 63, 100, 124] {
+            let mut data = vec![0u8; len];
+            rng.fill_bytes(&mut data);
+            let packed = Circuit::pack_bytes(&data);
+            assert_eq!(packed.len(), 1 + len.div_ceil(31));
+            assert_eq!(Circuit::unpack_bytes(&packed).unwrap(), data);
+
+            let cipher = Circuit::encrypt_bytes(&pk, &data, &params, &mut rng).unwrap();
+            assert_eq!(Circuit::decrypt_bytes(cipher, &sk, &params).unwrap(), data);
+        }
+        assert!(matches!(
+            Circuit::encrypt_bytes(&pk, &[0xff; 125], &params, &mut rng),
+            Err(EncryptionError::LengthMismatch {
+                expected: 5,
+                got: 6
+            })
+        ));
+
+        let packed = Circuit::pack_bytes(&[0xab; 40]);
+        let mut wrong_length = packed.clone();
+        wrong_length[0] = Fq::from(20u64);
+        let mut wide_chunk = packed.clone();
+        wide_chunk[1] += Fq::from(2u64).pow([8 * 31]);
+        let mut padding = packed.clone();
+        padding[2] += Fq::from(2u64).pow([8 * 30]);
+        for invalid in [vec![], wrong_length, wide_chunk, padding] {
+            assert!(matches!(
+                Circuit::unpack_bytes(&invalid),
+                Err(EncryptionError::InvalidPadding)
+            ));
+        }
+    }
+
+    #[test]
+    fn plaintext_longer_than_n_is_an_error() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::one(); 3];
+
+        assert!(matches!(
+            Circuit::new(pk, msg.clone(), params.clone(), &mut rng),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+
+        let mut circuit = Circuit::new(pk, msg[..2].to_vec(), params, &mut rng).unwrap();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let pk_var = JubJubVar::new_witness(cs.clone(), || Ok(pk)).unwrap();
+        let ciphertext = circuit
+            .ciphertext_var(cs.clone(), AllocationMode::Input)
+            .unwrap();
+        let len = FpVar::constant(Fq::from(2u64));
+        let plaintext = vec![FpVar::one(); 3];
+        assert!(matches!(
+            circuit.verify_encryption(
+                cs.clone(),
+                &pk_var,
+                &plaintext,
+                &ciphertext,
+                &len,
+                &[],
+                &mut ComponentCounter::new(cs),
+            ),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+
+        circuit.msg = msg;
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        assert!(matches!(
+            circuit.generate_constraints(cs),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
+    #[test]
+    fn keygen_from_seed_is_deterministic() {
+        let seed = [7u8; 32];
+        let (sk, pk) = Circuit::keygen_from_seed(&seed).unwrap();
+        let (sk_again, pk_again) = Circuit::keygen_from_seed(&seed).unwrap();
+        assert_eq!(sk.0, sk_again.0);
+        assert_eq!(pk, pk_again);
+        assert_eq!(JubJub::prime_subgroup_generator().mul(sk.0.into_repr()), pk);
+        assert_eq!(
+            crate::keys::public_key_to_hex(&pk),
+            "0x45d134ce9fc418944e78eb728eb35e52fc57a1f8c420b838e5f13c068e05cb15"
+        );
+    }
+
+    #[test]
+    fn low_order_public_keys_are_rejected() {
+        use ark_ed_on_bls12_381::EdwardsAffine;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        // (0, -1) is the point of order 2 on the twisted Edwards curve
+        let low_order: JubJub = EdwardsAffine::new(Fq::zero(), -Fq::one()).into();
+        assert!(low_order.into_affine().is_on_curve());
+        assert!(low_order.double().is_zero());
+
+        assert!(matches!(
+            Circuit::validate_public_key(&low_order),
+            Err(EncryptionError::PointNotOnCurve)
+        ));
+        assert!(matches!(
+            Circuit::validate_public_key(&JubJub::zero()),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        let r = Randomness::rand(&mut rng);
+        assert!(Circuit::encrypt(&low_order, &vec![Fq::one()], &r, &params).is_err());
+        assert!(Circuit::new(low_order, vec![Fq::one()], params, &mut rng).is_err());
+    }
+
+    #[test]
+    fn with_rate_only_accepts_the_hardcoded_rate() {
+        assert!(Parameters::<JubJub>::with_rate(2, POSEIDON_RATE).is_ok());
+        for rate in [1, 3, 4] {
+            assert!(matches!(
+                Parameters::<JubJub>::with_rate(2, rate),
+                Err(EncryptionError::InvalidParameters(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn wide_rates_decrypt_and_satisfy_the_circuit() {
+        type WideCircuit = EncryptCircuit<JubJub, JubJubVar, WidePoseidonHash>;
+
+        let mut rng = test_rng();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+        let r = Randomness::rand(&mut rng);
+
+        let mut constraints = Vec::new();
+        for rate in [2, 3, 4] {
+            let params = Parameters::<JubJub, WidePoseidonHash>::with_wide_rate(3, rate).unwrap();
+            assert_eq!(params.sponge.rate, rate);
+            let cipher = WideCircuit::encrypt(&pk, &msg, &r, &params).unwrap();
+            assert_eq!(WideCircuit::decrypt(cipher, &sk, &params).unwrap(), msg);
+
+            let circuit = WideCircuit::new(pk, msg.clone(), params, &mut rng).unwrap();
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+            constraints.push(cs.num_constraints());
+        }
+        // the point and the slot index fit a single absorption from rate 3 on
+        assert!(constraints[1] < constraints[0]);
+
+        let default = Parameters::<JubJub>::default_multi(3);
+        let wide = Parameters::<JubJub, WidePoseidonHash>::with_wide_rate(3, 2).unwrap();
+        assert_eq!(
+            WideCircuit::encrypt(&pk, &msg, &r, &wide).unwrap(),
+            Circuit::encrypt(&pk, &msg, &r, &default).unwrap()
+        );
+        assert!(matches!(
+            Parameters::<JubJub, WidePoseidonHash>::with_wide_rate(3, 0),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn constraint_count_grows_linearly_with_n() {
+        let metrics = |n| Circuit::constraint_metrics(&Parameters::default_multi(n)).unwrap();
+        let per_slot = metrics(2).num_constraints - metrics(1).num_constraints;
+        for n in [4, 8] {
+            assert_eq!(
+                metrics(n).num_constraints,
+                metrics(1).num_constraints + (n - 1) * per_slot
+            );
+            // one public c2 slot per slot of the message
+            assert_eq!(
+                metrics(n).num_instance_variables,
+                metrics(1).num_instance_variables + n - 1
+            );
+        }
+    }
+
+    #[test]
+    fn ciphertexts_under_the_same_randomness_add_up() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+        let a = vec![Fq::from(10u64), Fq::from(20u64)];
+        let b = vec![Fq::from(1u64), -Fq::from(2u64)];
+
+        let ca = Circuit::encrypt(&pk, &a, &r, &params).unwrap();
+        let cb = Circuit::encrypt(&pk, &b, &r, &params).unwrap();
+        let sum = Circuit::add_ciphertexts(&ca, &cb, &pk, &r, &params).unwrap();
+        assert_eq!(
+            Circuit::decrypt(sum, &sk, &params).unwrap(),
+            vec![Fq::from(11u64), Fq::from(18u64)]
+        );
+
+        let other = Circuit::encrypt(&pk, &b, &Randomness::rand(&mut rng), &params).unwrap();
+        assert!(matches!(
+            Circuit::add_ciphertexts(&ca, &other, &pk, &r, &params),
+            Err(EncryptionError::RandomnessMismatch)
+        ));
+    }
+
+    #[test]
+    fn tampered_authenticated_ciphertexts_are_rejected() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(5u64), Fq::from(6u64)];
+        let r = Randomness::rand(&mut rng);
+
+        let cipher = Circuit::encrypt_authenticated(&pk, &msg, &r, &params).unwrap();
+        assert_eq!(cipher.1.len(), msg.len() + 1);
+        assert_eq!(
+            Circuit::decrypt_authenticated(cipher.clone(), &sk, &params).unwrap(),
+            msg
+        );
+
+        for slot in 0..cipher.1.len() {
+            let mut tampered = cipher.clone();
+            tampered.1[slot] += Fq::one();
+            assert!(matches!(
+                Circuit::decrypt_authenticated(tampered, &sk, &params),
+                Err(EncryptionError::AuthenticationFailed)
+            ));
+        }
+    }
+
+    #[test]
+    fn authenticated_ciphertexts_are_not_re_encrypted() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (new_sk, new_pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(2);
+        let r = Randomness::rand(&mut rng);
+
+        let cipher = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+        let rotated = Circuit::rotate_key(&sk, &new_pk, &[cipher], &params, &mut rng).unwrap();
+        assert_eq!(
+            Circuit::decrypt(rotated[0].clone(), &new_sk, &params).unwrap(),
+            msg
+        );
+
+        let authenticated = Circuit::encrypt_authenticated(&pk, &msg, &r, &params).unwrap();
+        let too_long = |result| {
+            matches!(
+                result,
+                Err(EncryptionError::LengthMismatch {
+                    expected: 2,
+                    got: 3
+                })
+            )
+        };
+        assert!(too_long(
+            Circuit::rotate_key(
+                &sk,
+                &new_pk,
+                ark_std::slice::from_ref(&authenticated),
+                &params,
+                &mut rng
+            )
+            .map(|mut c| c.remove(0))
+        ));
+        assert!(too_long(Circuit::rerandomize(
+            &authenticated,
+            &sk,
+            &params,
+            &mut rng
+        )));
+        assert!(too_long(Circuit::add_ciphertexts(
+            &authenticated,
+            &authenticated,
+            &pk,
+            &r,
+            &params
+        )));
+    }
+
+    #[test]
+    fn associated_data_binds_the_ciphertext() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(8u64), Fq::from(9u64)];
+        let aad = vec![Fq::from(2024u64)];
+
+        let circuit =
+            Circuit::new_with_aad(pk, msg.clone(), aad.clone(), params.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+        assert_eq!(
+            Circuit::decrypt_with_aad(cipher.clone(), &aad, &sk, &params).unwrap(),
+            msg
+        );
+        assert_ne!(
+            Circuit::decrypt_with_aad(cipher.clone(), &[Fq::from(2025u64)], &sk, &params).unwrap(),
+            msg
+        );
+        assert_ne!(Circuit::decrypt(cipher.clone(), &sk, &params).unwrap(), msg);
+
+        // the circuit takes the associated data as public input after the ciphertext
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        let instance = cs.borrow().unwrap().instance_assignment[1..].to_vec();
+        assert_eq!(
+            instance,
+            Circuit::get_public_inputs_with_aad::<crate::Bls12_381>(&cipher, &aad, &params)
+        );
+    }
+
+    #[test]
+    fn decrypting_a_slot_out_of_range_is_an_error() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+        let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+
+        let masks = Circuit::slot_masks(&cipher, &sk, &params);
+        for i in 0..3 {
+            assert_eq!(
+                Circuit::decrypt_at(&cipher, i, &sk, &params).unwrap(),
+                msg[i]
+            );
+            assert_eq!(
+                Circuit::decrypt_at_with_dh(&cipher, i, &masks[i]).unwrap(),
+                msg[i]
+            );
+        }
+        // the reported length mustn't overflow for the largest index
+        for idx in [3, usize::MAX] {
+            assert!(matches!(
+                Circuit::decrypt_at(&cipher, idx, &sk, &params),
+                Err(EncryptionError::LengthMismatch { expected: 3, .. })
+            ));
+            assert!(matches!(
+                Circuit::decrypt_at_with_dh(&cipher, idx, &masks[0]),
+                Err(EncryptionError::LengthMismatch { expected: 3, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn precomputed_masks_decrypt_every_slot_like_decrypt() {
+        let mut rng = test_rng();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(5);
+        let derivations = [
+            MaskDerivation::Indexed,
+            MaskDerivation::Squeezed,
+            MaskDerivation::Chunked(NonZeroUsize::new(2).unwrap()),
+        ];
+        for (mask_derivation, bind_key) in derivations.into_iter().zip([false, true, true]) {
+            let params = Parameters {
+                mask_derivation,
+                bind_key,
+                ..Parameters::<JubJub>::default_multi(5)
+            };
+            let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+
+            let masks = Circuit::slot_masks(&cipher, &sk, &params);
+            assert_eq!(masks.len(), 5);
+            let slots = (0..5)
+                .map(|i| Circuit::decrypt_at_with_dh(&cipher, i, &masks[i]).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(slots, Circuit::decrypt(cipher, &sk, &params).unwrap());
+            assert_eq!(slots, msg);
+        }
+    }
+
+    #[test]
+    fn decrypt_range_matches_full_decryption() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(6);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = (1..=6u64).map(Fq::from).collect::<Vec<_>>();
+
+        let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+        let full = Circuit::decrypt(cipher.clone(), &sk, &params).unwrap();
+        for range in [0..6, 2..5, 5..6, 3..3] {
+            assert_eq!(
+                Circuit::decrypt_range(&cipher, range.clone(), &sk, &params).unwrap(),
+                full[range]
+            );
+        }
+        assert!(Circuit::decrypt_range(&cipher, 4..7, &sk, &params).is_err());
+    }
+
+    #[test]
+    fn deserialized_parameters_derive_the_same_masks() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::builder()
+            .n(3)
+            .domain_tag(Fq::from(17u64))
+            .bind_key(true)
+            .build()
+            .unwrap();
+        let mut bytes = Vec::new();
+        params.serialize(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), params.serialized_size());
+        let back = Parameters::<JubJub>::deserialize(&bytes[..]).unwrap();
+        assert_eq!(back.compat_hash(), params.compat_hash());
+
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(1u64), Fq::from(2u64), Fq::from(3u64)];
+        let r = Randomness::rand(&mut rng);
+        let cipher = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+        assert_eq!(Circuit::encrypt(&pk, &msg, &r, &back).unwrap(), cipher);
+        assert_eq!(Circuit::decrypt(cipher, &sk, &back).unwrap(), msg);
+
+        assert!(Parameters::<JubJub>::deserialize(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn length_prefixed_padding_keeps_legitimate_zeros() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(5);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+
+        for msg in [
+            vec![Fq::zero(), Fq::zero(), Fq::from(5u64)],
+            vec![Fq::from(5u64), Fq::zero(), Fq::zero()],
+            vec![],
+        ] {
+            let circuit = Circuit::new_padded(pk, msg.clone(), params.clone(), &mut rng).unwrap();
+            let cipher = circuit.resulted_ciphertext.clone();
+            assert_eq!(Circuit::decrypt_padded(cipher, &sk, &params).unwrap(), msg);
+
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // a prefix claiming fewer slots than the payload holds has non-zero padding
+        let lying = vec![Fq::from(2u64), Fq::zero(), Fq::zero(), Fq::from(5u64)];
+        let mut circuit = Circuit::new(pk, lying, params.clone(), &mut rng).unwrap();
+        circuit.length_prefixed = true;
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        let too_long = vec![Fq::one(); params.n];
+        assert!(Circuit::new_padded(pk, too_long, params, &mut rng).is_err());
+    }
+
+    #[test]
+    fn padding_slots_above_the_length_must_be_zero() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(4);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(3u64), Fq::from(4u64)];
+        let circuit = Circuit::new(pk, msg.clone(), params, &mut rng).unwrap();
+        let (c1, c2) = circuit.resulted_ciphertext.clone();
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        // synthesizes the checks of a ciphertext of length 2 against the given padded slots
+        let satisfied = |plaintext: [Fq; 4], c2: [Fq; 4]| {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let pk = JubJubVar::new_witness(cs.clone(), || Ok(pk)).unwrap();
+            let c1 = JubJubVar::new_input(cs.clone(), || Ok(c1)).unwrap();
+            let c2 = Vec::<FpVar<Fq>>::new_input(cs.clone(), || Ok(c2.to_vec())).unwrap();
+            let len = FpVar::new_input(cs.clone(), || Ok(Fq::from(2u64))).unwrap();
+            let plaintext = Vec::<FpVar<Fq>>::new_witness(cs.clone(), || Ok(plaintext)).unwrap();
+            circuit
+                .verify_encryption(
+                    cs.clone(),
+                    &pk,
+                    &plaintext,
+                    &(c1, c2),
+                    &len,
+                    &[],
+                    &mut ComponentCounter::new(cs.clone()),
+                )
+                .unwrap();
+            cs.is_satisfied().unwrap()
+        };
+
+        let zero = Fq::zero();
+        let junk = Fq::from(9u64);
+        assert!(satisfied(
+            [msg[0], msg[1], zero, zero],
+            [c2[0], c2[1], zero, zero]
+        ));
+        assert!(!satisfied(
+            [msg[0], msg[1], zero, junk],
+            [c2[0], c2[1], zero, zero]
+        ));
+        assert!(!satisfied(
+            [msg[0], msg[1], zero, zero],
+            [c2[0], c2[1], junk, zero]
+        ));
+        // a zero ciphertext slot within the length doesn't skip the check of its plaintext
+        assert!(!satisfied(
+            [junk, msg[1], zero, zero],
+            [zero, c2[1], zero, zero]
+        ));
+    }
+
+    #[test]
+    fn proofs_verify_through_a_single_call() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+
+        let (cipher, proof) =
+            Circuit::encrypt_and_prove(&pk, &message(1), &params, &proving_key, &mut rng).unwrap();
+        assert!(Circuit::verify_proof(&vk, &cipher, &proof, &params).unwrap());
+
+        let mut tampered = cipher.clone();
+        tampered.1[0] += Fq::one();
+        assert!(!Circuit::verify_proof(&vk, &tampered, &proof, &params).unwrap());
+        let mut longer = cipher.clone();
+        longer.1.push(Fq::one());
+        assert!(!Circuit::verify_proof(&vk, &longer, &proof, &params).unwrap());
+        // parameters of another size don't match the inputs of the verifying key
+        let wider = Parameters::<JubJub>::default_multi(2);
+        assert!(matches!(
+            Circuit::verify_proof(&vk, &cipher, &proof, &wider),
+            Err(EncryptionError::SynthesisFailed(_))
+        ));
+    }
+
+    #[test]
+    fn batch_proofs_verify_against_their_ciphertexts() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let circuits = (1..=3u64)
+            .map(|i| Circuit::new(pk, vec![Fq::from(i)], params.clone(), &mut rng).unwrap())
+            .collect::<Vec<_>>();
+
+        let proofs = Circuit::prove_batch(&proving_key, &circuits, &mut rng).unwrap();
+        assert_eq!(proofs.len(), circuits.len());
+        for (circuit, proof) in circuits.iter().zip(&proofs) {
+            assert!(Circuit::verify(&vk, &circuit.resulted_ciphertext, proof, &params).unwrap());
+        }
+        // each proof is drawn under its own randomness
+        assert_ne!(proofs[0].a, proofs[1].a);
+        assert!(
+            !Circuit::verify(&vk, &circuits[0].resulted_ciphertext, &proofs[1], &params).unwrap()
+        );
+        assert!(Circuit::prove_batch(&proving_key, &[], &mut rng)
+            .unwrap()
+            .is_empty());
+
+        // a circuit that fails to synthesize fails the whole batch
+        let mut invalid = circuits[0].clone();
+        invalid.msg = message(2);
+        assert!(matches!(
+            Circuit::prove_batch(&proving_key, &[circuits[1].clone(), invalid], &mut rng),
+            Err(EncryptionError::SynthesisFailed(
+                SynthesisError::Unsatisfiable
+            ))
+        ));
+    }
+
+    #[test]
+    fn a_batch_with_one_invalid_proof_is_rejected() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let mut items = (1..=3u64)
+            .map(|i| {
+                Circuit::encrypt_and_prove(&pk, &vec![Fq::from(i)], &params, &proving_key, &mut rng)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(Circuit::verify_batch(&vk, &items, &params).unwrap());
+        assert!(Circuit::verify_batch(&vk, &[], &params).unwrap());
+
+        items[1].1 = items[2].1.clone();
+        assert!(!Circuit::verify_batch(&vk, &items, &params).unwrap());
+        assert!(Circuit::verify_batch(&vk, &items[..1], &params).unwrap());
+
+        let wider = Parameters::<JubJub>::default_multi(2);
+        assert!(Circuit::verify_batch(&vk, &items, &wider).is_err());
+    }
+
+    #[test]
+    fn seeded_setups_are_reproducible() {
+        use crate::artifacts::serialize_vk;
+        use crate::Bls12_381;
+
+        let params = Parameters::<JubJub>::default_multi(1);
+        let setup = |seed| {
+            Circuit::setup::<Bls12_381, _>(&params, &mut ChaCha20Rng::from_seed(seed)).unwrap()
+        };
+        let (proving_key, vk) = setup([1; 32]);
+        let (_, same_vk) = setup([1; 32]);
+        let (_, other_vk) = setup([2; 32]);
+        assert_eq!(serialize_vk(&vk).unwrap(), serialize_vk(&same_vk).unwrap());
+        assert_ne!(serialize_vk(&vk).unwrap(), serialize_vk(&other_vk).unwrap());
+
+        let mut rng = test_rng();
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (cipher, proof) =
+            Circuit::encrypt_and_prove(&pk, &message(1), &params, &proving_key, &mut rng).unwrap();
+        assert!(Circuit::verify(&same_vk, &cipher, &proof, &params).unwrap());
+        assert!(!Circuit::verify(&other_vk, &cipher, &proof, &params).unwrap());
+    }
+
+    #[test]
+    fn predicted_ciphertext_sizes_match_the_serialization() {
+        let mut rng = test_rng();
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        for n in [1, 2, 5, 16] {
+            let params = Parameters::<JubJub>::default_multi(n);
+            let cipher =
+                Circuit::encrypt(&pk, &message(n), &Randomness::rand(&mut rng), &params).unwrap();
+            let size = Circuit::ciphertext_serialized_size(&params);
+            assert_eq!(size, 32 + 8 + 32 * n);
+            assert_eq!(Circuit::ciphertext_to_bytes(&cipher).len(), size);
+        }
+    }
+
+    #[test]
+    fn fixed_randomness_yields_a_stable_ciphertext() {
+        use ark_ed_on_bls12_381::Fr;
+
+        let (_, pk) = Circuit::keygen_from_seed(&[3; 32]).unwrap();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let r = Randomness::<JubJub>::from_scalar(Fr::from(7u64));
+        assert_eq!(*r.as_scalar(), Fr::from(7u64));
+
+        let cipher = Circuit::encrypt(&pk, &message(2), &r, &params).unwrap();
+        assert_eq!(
+            cipher,
+            Circuit::encrypt(&pk, &message(2), &r.clone(), &params).unwrap()
+        );
+        assert_eq!(
+            cipher.0,
+            JubJub::prime_subgroup_generator().mul(Fr::from(7u64).into_repr())
+        );
+
+        assert!(matches!(
+            Circuit::encrypt(
+                &pk,
+                &message(2),
+                &Randomness::from_scalar(Fr::zero()),
+                &params
+            ),
+            Err(EncryptionError::ZeroRandomness)
+        ));
+    }
+
+    #[test]
+    fn precomputed_generator_multiplication_matches_and_saves_constraints() {
+        use ark_ed_on_bls12_381::Fr;
+
+        let mut rng = test_rng();
+        let g = JubJub::prime_subgroup_generator();
+        for r in [Fr::zero(), Fr::one(), Fr::rand(&mut rng)] {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let bits = Circuit::scalar_bits_var(cs.clone(), &r).unwrap();
+            let before = cs.num_constraints();
+            let precomputed = Circuit::generator_mul_var(&bits, &g).unwrap();
+            assert_eq!(precomputed.value().unwrap(), g.mul(r.into_repr()));
+            let optimized = cs.num_constraints() - before;
+
+            let before = cs.num_constraints();
+            let generic = JubJubVar::new_constant(cs.clone(), g)
+                .unwrap()
+                .scalar_mul_le(bits.iter())
+                .unwrap();
+            assert_eq!(generic.value().unwrap(), precomputed.value().unwrap());
+            assert!(optimized < cs.num_constraints() - before);
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // the circuit ties the public c1 to the witnessed randomness through the lookups
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let mut circuit = Circuit::new(pk, message(1), params, &mut rng).unwrap();
+        circuit.resulted_ciphertext.0 += g;
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn malformed_ciphertext_bytes_are_errors() {
+        use ark_ed_on_bls12_381::EdwardsAffine;
+        use ark_ff::SquareRootField;
+        use ark_std::rand::RngCore;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let cipher =
+            Circuit::encrypt(&pk, &message(3), &Randomness::rand(&mut rng), &params).unwrap();
+        let bytes = Circuit::ciphertext_to_bytes(&cipher);
+        assert_eq!(
+            Circuit::ciphertext_from_bytes(&bytes, &params).unwrap(),
+            cipher
+        );
+
+        for len in 0..bytes.len() {
+            assert!(Circuit::ciphertext_from_bytes(&bytes[..len], &params).is_err());
+        }
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(Circuit::ciphertext_from_bytes(&trailing, &params).is_err());
+        assert!(matches!(
+            Circuit::ciphertext_from_bytes(&bytes, &Parameters::<JubJub>::default_multi(2)),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+
+        // a non-canonical slot and a c1 outside the prime-order subgroup
+        let mut slot_overflow = bytes.clone();
+        slot_overflow[40..72].fill(0xff);
+        let order_four = EdwardsAffine::new((-Fq::one()).sqrt().unwrap(), Fq::zero());
+        let off_subgroup =
+            Circuit::ciphertext_to_bytes(&(cipher.0 + JubJub::from(order_four), cipher.1));
+        for invalid in [slot_overflow, off_subgroup] {
+            assert!(Circuit::ciphertext_from_bytes(&invalid, &params).is_err());
+        }
+
+        for len in [0, 1, 8, 40, 72, 136, 200] {
+            for _ in 0..32 {
+                let mut random = vec![0u8; len];
+                rng.fill_bytes(&mut random);
+                assert!(Circuit::ciphertext_from_bytes(&random, &params).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn keypairs_verify_only_when_they_match() {
+        let mut rng = test_rng();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (other_sk, other_pk) = Circuit::keygen(&mut rng).unwrap();
+
+        assert!(Circuit::verify_keypair(&sk, &pk));
+        assert!(Circuit::verify_keypair(&other_sk, &other_pk));
+        assert!(!Circuit::verify_keypair(&sk, &other_pk));
+        assert!(!Circuit::verify_keypair(&other_sk, &pk));
+        assert!(!Circuit::verify_keypair(&sk, &-pk));
+    }
+
+    #[test]
+    fn recovered_shared_points_reproduce_the_masks() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+        let cipher = Circuit::encrypt(&pk, &message(3), &r, &params).unwrap();
+
+        let s = Circuit::recover_shared_point(&cipher, &sk);
+        assert_eq!(
+            s,
+            Circuit::compute_shared_secret(&pk, &r)
+                .unwrap()
+                .into_affine()
+        );
+        let sponge = Circuit::kdf_sponge(&s, None, &[], &params);
+        let masks = Circuit::slot_masks_of(&sponge, 0..3, &params);
+        let msg = cipher
+            .1
+            .iter()
+            .zip(masks)
+            .map(|(c2i, dh)| *c2i - dh)
+            .collect::<Vec<_>>();
+        assert_eq!(msg, Circuit::decrypt(cipher.clone(), &sk, &params).unwrap());
+        assert_eq!(msg, message(3));
+
+        let (other_sk, _) = Circuit::keygen(&mut rng).unwrap();
+        assert_ne!(Circuit::recover_shared_point(&cipher, &other_sk), s);
+    }
+
+    #[test]
+    fn encrypting_into_a_buffer_matches_encrypt() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(4);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+
+        // the buffer starts out longer than the messages and its contents get replaced
+        let mut out = vec![Fq::one(); 6];
+        for len in [4, 2, 3] {
+            let r = Randomness::rand(&mut rng);
+            let c1 = Circuit::encrypt_into(&pk, &message(len), &r, &params, &mut out).unwrap();
+            assert_eq!(
+                (c1, out.clone()),
+                Circuit::encrypt(&pk, &message(len), &r, &params).unwrap()
+            );
+        }
+
+        let r = Randomness::rand(&mut rng);
+        assert!(matches!(
+            Circuit::encrypt_into(&JubJub::zero(), &message(2), &r, &params, &mut out),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        assert!(matches!(
+            Circuit::encrypt_into(
+                &pk,
+                &message(2),
+                &Randomness::from_scalar(Zero::zero()),
+                &params,
+                &mut out
+            ),
+            Err(EncryptionError::ZeroRandomness)
+        ));
+    }
 
-        // compute s = c1^secret_key
-        let mut s = c1;
-        s.mul_assign(sk);
-        let sa = s.into_affine();
+    #[test]
+    fn native_and_in_circuit_parameter_sponges_agree() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let point = JubJub::rand(&mut rng);
+        let data = message(3);
 
-        // compute dh = H(s)
-        let mut sponge = PoseidonSponge::new(&params.poseidon);
-        sponge.absorb(&sa);
-        let dh = sponge.squeeze_field_elements::<C::BaseField>(1).remove(0);
+        let mut sponge = params.new_sponge();
+        sponge.absorb(&point.into_affine());
+        sponge.absorb(&data);
+        let native = sponge.squeeze_field_elements::<Fq>(3);
 
-        // compute message = c2 - dh
-        Ok(c2.into_iter().map(|c2i| c2i - dh).collect())
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let mut sponge_var = params.new_sponge_var(cs.clone());
+        let point_var = JubJubVar::new_witness(cs.clone(), || Ok(point)).unwrap();
+        let data_var = Vec::<FpVar<Fq>>::new_witness(cs.clone(), || Ok(data.clone())).unwrap();
+        sponge_var.absorb(&point_var).unwrap();
+        sponge_var.absorb(&data_var).unwrap();
+        let in_circuit = sponge_var.squeeze_field_elements(3).unwrap();
+        assert_eq!(in_circuit.value().unwrap(), native);
+        assert!(cs.is_satisfied().unwrap());
+
+        // the sponges continue from the domain tag like the KDF does
+        let tagged = Parameters {
+            domain_tag: Some(Fq::from(7u64)),
+            ..params.clone()
+        };
+        let s = point.into_affine();
+        let kdf = Circuit::kdf_sponge(&s, None, &[], &tagged);
+        let mut sponge = tagged.new_sponge();
+        sponge.absorb(&s);
+        sponge.absorb(&Fq::from(7u64));
+        sponge.absorb(&Vec::<Fq>::new());
+        assert_eq!(
+            kdf.clone().squeeze_field_elements::<Fq>(1),
+            sponge.squeeze_field_elements::<Fq>(1)
+        );
+        assert_ne!(
+            kdf.clone().squeeze_field_elements::<Fq>(1),
+            params.new_sponge().squeeze_field_elements::<Fq>(1)
+        );
     }
 
-    pub fn decrypt_at(
-        cipher: &Ciphertext<C>,
-        idx: usize,
-        sk: SecretKey<C>,
-        params: &Parameters<C>,
-    ) -> anyhow::Result<C::BaseField> {
-        let c1 = cipher.0;
-        let c2 = cipher.1[idx].clone();
+    #[test]
+    fn public_input_arity_matches_the_inputs_and_the_circuit() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        for (n, domain_tag) in [(1, None), (2, Some(Fq::from(5u64))), (7, None)] {
+            let params = Parameters {
+                domain_tag,
+                ..Parameters::<JubJub>::default_multi(n)
+            };
+            let arity = Circuit::num_public_inputs::<Bls12_381>(&params);
+            assert_eq!(arity, 2 + n + 1 + usize::from(domain_tag.is_some()));
+
+            // a short ciphertext is padded up to `n` slots
+            let cipher =
+                Circuit::encrypt(&pk, &message(1), &Randomness::rand(&mut rng), &params).unwrap();
+            assert_eq!(
+                Circuit::get_public_inputs::<Bls12_381>(&cipher, &params).len(),
+                arity
+            );
+            // the constraint system has the arity plus the constant one
+            assert_eq!(
+                Circuit::constraint_metrics(&params)
+                    .unwrap()
+                    .num_instance_variables,
+                arity + 1
+            );
+        }
+    }
+
+    #[test]
+    fn hybrid_encryption_round_trips_large_blobs() {
+        use ark_std::rand::RngCore;
 
-        // compute s = c1^secret_key
-        let mut s = c1;
-        s.mul_assign(sk);
-        let sa = s.into_affine();
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let mut data = vec![0u8; 4096 + 13];
+        rng.fill_bytes(&mut data);
 
-        // compute dh = H(s)
-        let mut sponge = PoseidonSponge::new(&params.poseidon);
-        sponge.absorb(&sa);
-        let dh = sponge.squeeze_field_elements::<C::BaseField>(1).remove(0);
+        let (cipher, blob) = Circuit::hybrid_encrypt(&pk, &data, &params, &mut rng).unwrap();
+        assert_eq!(blob.len(), data.len());
+        assert_ne!(blob, data);
+        assert_eq!(
+            Circuit::hybrid_decrypt(cipher.clone(), &blob, &sk, &params).unwrap(),
+            data
+        );
+        // fresh seeds key distinct keystreams
+        let (_, other_blob) = Circuit::hybrid_encrypt(&pk, &data, &params, &mut rng).unwrap();
+        assert_ne!(other_blob, blob);
+        let (empty_cipher, empty) = Circuit::hybrid_encrypt(&pk, &[], &params, &mut rng).unwrap();
+        assert!(Circuit::hybrid_decrypt(empty_cipher, &empty, &sk, &params)
+            .unwrap()
+            .is_empty());
 
-        // compute message = c2 - dh
-        Ok(c2 - dh)
+        let (other_sk, _) = Circuit::keygen(&mut rng).unwrap();
+        assert_ne!(
+            Circuit::hybrid_decrypt(cipher.clone(), &blob, &other_sk, &params).unwrap(),
+            data
+        );
+        let mut two_slots = cipher;
+        two_slots.1.push(Fq::one());
+        assert!(matches!(
+            Circuit::hybrid_decrypt(two_slots, &blob, &sk, &params),
+            Err(EncryptionError::LengthMismatch {
+                expected: 1,
+                got: 2
+            })
+        ));
     }
 
-    pub(crate) fn verify_encryption(
-        &self,
-        cs: ConstraintSystemRef<C::BaseField>,
-        plaintext: &Vec<FpVar<C::BaseField>>,
-        ciphertext: &(CV, Vec<FpVar<C::BaseField>>),
-    ) -> Result<(), SynthesisError> {
-        assert!(self.params.n >= plaintext.len());
-        assert!(self.params.n >= ciphertext.1.len());
+    #[test]
+    fn key_bound_ciphertexts_only_decrypt_under_the_same_binding() {
+        let mut rng = test_rng();
+        let unbound = Parameters::<JubJub>::default_multi(3);
+        let bound = Parameters {
+            bind_key: true,
+            ..unbound.clone()
+        };
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+        let r = Randomness::rand(&mut rng);
+
+        let bound_cipher = Circuit::encrypt(&pk, &msg, &r, &bound).unwrap();
+        let unbound_cipher = Circuit::encrypt(&pk, &msg, &r, &unbound).unwrap();
+        assert_eq!(bound_cipher.0, unbound_cipher.0);
+        assert_ne!(bound_cipher.1, unbound_cipher.1);
+        assert_eq!(
+            Circuit::decrypt(bound_cipher.clone(), &sk, &bound).unwrap(),
+            msg
+        );
+        assert_ne!(
+            Circuit::decrypt(bound_cipher.clone(), &sk, &unbound).unwrap(),
+            msg
+        );
+        assert_ne!(Circuit::decrypt(unbound_cipher, &sk, &bound).unwrap(), msg);
 
-        let g = CV::new_constant(ns!(cs, "generator"), C::prime_subgroup_generator())?;
+        let circuit = Circuit::new(pk, msg.clone(), bound.clone(), &mut rng).unwrap();
+        assert_eq!(
+            Circuit::decrypt(circuit.resulted_ciphertext.clone(), &sk, &bound).unwrap(),
+            msg
+        );
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
 
-        // flatten randomness to little-endian bit vector
-        let r = to_bytes![&self.r.0].unwrap();
-        let randomness = UInt8::new_witness_vec(ns!(cs, "encryption_randomness"), &r)?
+        let s = Circuit::recover_shared_point(&bound_cipher, &sk);
+        assert!(matches!(
+            Circuit::decrypt_with_shared_point(bound_cipher, &JubJub::from(s), &bound),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn decrypt_iter_decrypts_lazily_like_decrypt() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let ciphers = (1..=3)
+            .map(|len| Circuit::encrypt(&pk, &message(len), &Randomness::rand(&mut rng), &params))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let decrypted = Circuit::decrypt_iter(ciphers.clone().into_iter(), sk.clone(), &params)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let expected = ciphers
             .iter()
-            .flat_map(|b| b.to_bits_le().unwrap())
+            .map(|cipher| Circuit::decrypt(cipher.clone(), &sk, &params).unwrap())
             .collect::<Vec<_>>();
+        assert_eq!(decrypted, expected);
+        assert_eq!(decrypted[2], message(3));
 
-        let pk = CV::new_witness(ns!(cs, "pub_key"), || Ok(self.pk.clone()))?;
+        let pulled = AtomicUsize::new(0);
+        let source = ciphers.into_iter().inspect(|_| {
+            pulled.fetch_add(1, Ordering::SeqCst);
+        });
+        let mut lazy = Circuit::decrypt_iter(source, sk, &params);
+        assert_eq!(pulled.load(Ordering::SeqCst), 0);
+        assert_eq!(lazy.next().unwrap().unwrap(), expected[0]);
+        assert_eq!(pulled.load(Ordering::SeqCst), 1);
+    }
 
-        // compute s = randomness*pk
-        let s = pk.clone().scalar_mul_le(randomness.iter())?;
+    #[test]
+    fn encapsulated_keys_decapsulate_and_mask_like_encrypt() {
+        let mut rng = test_rng();
+        let m = Fq::from(42u64);
+        for bind_key in [false, true] {
+            let params = Parameters {
+                bind_key,
+                ..Parameters::<JubJub>::default_multi(1)
+            };
+            let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
 
-        // compute c1 = randomness*generator
-        let c1 = g.clone().scalar_mul_le(randomness.iter())?;
+            let mut replay = rng.clone();
+            let (c1, dh) = Circuit::encapsulate(&pk, &params, &mut rng).unwrap();
+            assert_eq!(Circuit::decapsulate(&c1, &sk, &params).unwrap(), dh);
 
-        let mut poseidon = PoseidonSpongeVar::new(cs.clone(), &self.params.poseidon);
-        poseidon.absorb(&s)?;
-        let dh = poseidon
-            .squeeze_field_elements(1)
-            .and_then(|r| Ok(r[0].clone()))?;
+            let r = Randomness::rand(&mut replay);
+            assert_eq!(
+                Circuit::encrypt(&pk, &vec![m], &r, &params).unwrap(),
+                (c1, vec![dh + m])
+            );
+        }
 
-        c1.enforce_equal(&ciphertext.0)?;
+        let params = Parameters::<JubJub>::default_multi(1);
+        assert!(matches!(
+            Circuit::encapsulate(&JubJub::zero(), &params, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
 
-        plaintext
-            .into_iter()
-            .map(|m| dh.clone() + m)
-            .zip(ciphertext.1.iter())
-            .map(|(c2, exp)| {
-                let is_not_empty = exp.is_zero().unwrap().not();
-                c2.conditional_enforce_equal(&exp, &is_not_empty)
+    #[test]
+    fn checked_public_inputs_reject_ciphertexts_longer_than_n() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let cipher =
+            Circuit::encrypt(&pk, &message(2), &Randomness::rand(&mut rng), &params).unwrap();
+
+        assert_eq!(
+            Circuit::try_get_public_inputs::<Bls12_381>(&cipher, &params).unwrap(),
+            Circuit::get_public_inputs::<Bls12_381>(&cipher, &params)
+        );
+        let mut short = cipher.clone();
+        short.1.pop();
+        assert_eq!(
+            Circuit::try_get_public_inputs::<Bls12_381>(&short, &params).unwrap(),
+            Circuit::get_public_inputs::<Bls12_381>(&short, &params)
+        );
+
+        let mut long = cipher;
+        long.1.push(Fq::one());
+        assert!(matches!(
+            Circuit::try_get_public_inputs::<Bls12_381>(&long, &params),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
             })
-            .collect::<Result<Vec<_>, _>>()
-            .map(|_| ())
+        ));
     }
 
-    pub(crate) fn ciphertext_var(
-        &self,
-        cs: ConstraintSystemRef<C::BaseField>,
-        mode: AllocationMode,
-    ) -> Result<(CV, Vec<FpVar<C::BaseField>>), SynthesisError> {
-        let c1 = CV::new_variable(
-            ns!(cs, "ciphertext"),
-            || Ok(self.resulted_ciphertext.0),
-            mode,
-        )?;
-        let c2 = (0..self.params.n)
-            .map(|i| {
-                FpVar::<C::BaseField>::new_variable(
-                    ns!(cs, "ciphertext"),
-                    || {
-                        Ok(self
-                            .resulted_ciphertext
-                            .1
-                            .get(i)
-                            .map_or(C::BaseField::zero(), |c| *c))
-                    },
-                    mode,
-                )
+    #[test]
+    fn debug_output_redacts_the_randomness_and_the_plaintext() {
+        use ark_std::format;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::rand(&mut rng), Fq::rand(&mut rng)];
+        let circuit = Circuit::new(pk, msg.clone(), params, &mut rng).unwrap();
+
+        let debug = format!("{circuit:?}");
+        assert!(debug.contains("<redacted>"));
+        assert!(debug.contains(&format!("{:?}", pk.into_affine())));
+        assert!(debug.contains("n: 2"));
+        assert!(!debug.contains(&format!("{:?}", circuit.r.0)));
+        assert!(!debug.contains(&format!("{}", circuit.r.0)));
+        for m in msg {
+            assert!(!debug.contains(&format!("{m:?}")));
+            assert!(!debug.contains(&format!("{m}")));
+        }
+    }
+
+    #[test]
+    fn squeezed_masks_are_distinct_and_decrypt() {
+        let mut rng = test_rng();
+        let params = Parameters {
+            mask_derivation: MaskDerivation::Squeezed,
+            ..Parameters::<JubJub>::default_multi(5)
+        };
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+        let x = Fq::from(42u64);
+        let msg = vec![x; 5];
+
+        let (c1, c2) = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+        for i in 0..c2.len() {
+            for j in i + 1..c2.len() {
+                assert_ne!(c2[i], c2[j]);
+            }
+        }
+        let indexed = Parameters {
+            mask_derivation: MaskDerivation::Indexed,
+            ..params.clone()
+        };
+        assert_ne!(Circuit::encrypt(&pk, &msg, &r, &indexed).unwrap().1, c2);
+        assert_eq!(
+            Circuit::decrypt((c1, c2.clone()), &sk, &params).unwrap(),
+            msg
+        );
+        assert_ne!(Circuit::decrypt((c1, c2), &sk, &indexed).unwrap(), msg);
+
+        let mut circuit = Circuit::new(pk, msg, params, &mut rng).unwrap();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        circuit.resulted_ciphertext.1[4] += Fq::one();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[test]
+    fn default_parameters_validate_and_weak_ones_are_rejected() {
+        Parameters::<JubJub>::default().validate().unwrap();
+        for n in [1, 4, 16] {
+            Parameters::<JubJub>::default_multi(n).validate().unwrap();
+        }
+
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(&params.sponge);
+        let with_sponge =
+            |full: u32, partial: u32, alpha: u64, mds: Vec<Vec<Fq>>, ark: Vec<Vec<Fq>>| {
+                Parameters {
+                    sponge: PoseidonParameters::new(full, partial, alpha, mds, ark),
+                    ..params.clone()
+                }
+            };
+        let few_rounds = with_sponge(full_rounds, 2, alpha, mds.clone(), ark[..10].to_vec());
+        let odd_full_rounds = with_sponge(
+            full_rounds - 1,
+            partial_rounds,
+            alpha,
+            mds.clone(),
+            ark[1..].to_vec(),
+        );
+        let not_a_permutation =
+            with_sponge(full_rounds, partial_rounds, 3, mds.clone(), ark.clone());
+        let mut singular = mds;
+        singular[1] = singular[0].clone();
+        let not_mds = with_sponge(full_rounds, partial_rounds, alpha, singular, ark);
+        for weak in [few_rounds, odd_full_rounds, not_a_permutation, not_mds] {
+            assert!(matches!(
+                weak.validate(),
+                Err(EncryptionError::InvalidParameters(_))
+            ));
+        }
+
+        let identity_generator = Parameters {
+            generator: JubJub::zero(),
+            ..params
+        };
+        assert!(matches!(
+            identity_generator.validate(),
+            Err(EncryptionError::PointNotOnCurve)
+        ));
+    }
+
+    #[test]
+    fn custom_generators_key_encrypt_and_satisfy_the_circuit() {
+        use ark_ed_on_bls12_381::{EdwardsAffine, Fr};
+        use ark_ff::SquareRootField;
+
+        let mut rng = test_rng();
+        let generator = JubJub::prime_subgroup_generator().mul(Fr::from(7u64).into_repr());
+        let params = Parameters::<JubJub>::builder()
+            .n(2)
+            .generator(generator)
+            .build()
+            .unwrap();
+        let default_params = Parameters::<JubJub>::default_multi(2);
+
+        let (sk, pk) = Circuit::keygen_with_params(&params, &mut rng).unwrap();
+        assert!(Circuit::verify_keypair_with_params(&sk, &pk, &params));
+        assert!(!Circuit::verify_keypair_with_params(
+            &sk,
+            &pk,
+            &default_params
+        ));
+        assert!(!Circuit::verify_keypair(&sk, &pk));
+
+        let seed = [3u8; 32];
+        let (seeded_sk, seeded_pk) = Circuit::keygen_from_seed_with_params(&seed, &params).unwrap();
+        assert_eq!(seeded_sk.0, Circuit::keygen_from_seed(&seed).unwrap().0 .0);
+        assert!(Circuit::verify_keypair_with_params(
+            &seeded_sk, &seeded_pk, &params
+        ));
+        assert_eq!(
+            Circuit::keygen_from_seed_with_params(&seed, &params)
+                .unwrap()
+                .1,
+            seeded_pk
+        );
+
+        let msg = message(2);
+        let circuit = Circuit::new(pk, msg.clone(), params.clone(), &mut rng).unwrap();
+        assert_eq!(
+            Circuit::decrypt(circuit.resulted_ciphertext.clone(), &sk, &params).unwrap(),
+            msg
+        );
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let order_four = EdwardsAffine::new((-Fq::one()).sqrt().unwrap(), Fq::zero());
+        assert!(matches!(
+            Parameters::<JubJub>::builder()
+                .generator(order_four.into())
+                .build(),
+            Err(EncryptionError::PointNotOnCurve)
+        ));
+    }
+
+    #[test]
+    fn encrypt_and_prove_proves_its_own_ciphertext() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let msg = message(2);
+
+        let (cipher, proof) =
+            Circuit::encrypt_and_prove(&pk, &msg, &params, &proving_key, &mut rng).unwrap();
+        assert_eq!(Circuit::decrypt(cipher.clone(), &sk, &params).unwrap(), msg);
+        let inputs = Circuit::get_public_inputs::<Bls12_381>(&cipher, &params);
+        assert!(Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+
+        // the proof is bound to the returned ciphertext, not to another encryption of `msg`
+        let (other, _) =
+            Circuit::encrypt_and_prove(&pk, &msg, &params, &proving_key, &mut rng).unwrap();
+        let other_inputs = Circuit::get_public_inputs::<Bls12_381>(&other, &params);
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &other_inputs, &proof).unwrap());
+
+        assert!(matches!(
+            Circuit::encrypt_and_prove(&pk, &message(3), &params, &proving_key, &mut rng),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
             })
-            .collect::<Result<_, _>>()?;
+        ));
+        assert!(matches!(
+            Circuit::encrypt_and_prove(&JubJub::zero(), &msg, &params, &proving_key, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
 
-        Ok((c1, c2))
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn constraint_breakdown_sums_to_the_total() {
+        for n in [1, 3] {
+            let params = Parameters::<JubJub>::default_multi(n);
+            let breakdown = Circuit::constraint_breakdown(&params).unwrap();
+            assert_eq!(
+                breakdown.components.iter().map(|(_, c)| c).sum::<usize>(),
+                breakdown.num_constraints
+            );
+            assert_eq!(
+                breakdown.num_constraints,
+                Circuit::constraint_metrics(&params)
+                    .unwrap()
+                    .num_constraints
+            );
+
+            let names = breakdown
+                .components
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>();
+            for name in ["allocation", "shared_secret", "generator_mul", "slot_masks"] {
+                assert!(names.contains(&name), "{name} missing from {names:?}");
+            }
+            let count = |name| {
+                breakdown
+                    .components
+                    .iter()
+                    .find(|(c, _)| *c == name)
+                    .unwrap()
+                    .1
+            };
+            assert!(count("shared_secret") > 0);
+            assert!(count("slot_masks") > 0);
+        }
     }
-}
 
-impl<C, CV> ConstraintSynthesizer<C::BaseField> for EncryptCircuit<C, CV>
-where
-    C: ProjectiveCurve,
-    C::BaseField: PrimeField,
-    C::Affine: Absorb,
-    C::BaseField: Absorb,
-    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
-{
-    fn generate_constraints(
-        self,
-        cs: ConstraintSystemRef<C::BaseField>,
-    ) -> Result<(), SynthesisError> {
-        let message = (0..self.params.n)
+    #[test]
+    fn signed_values_round_trip_through_the_centered_encoding() {
+        let values = [
+            0,
+            1,
+            -1,
+            42,
+            -42,
+            i64::MAX,
+            i64::MIN,
+            i64::MAX - 1,
+            i64::MIN + 1,
+        ];
+        for v in values {
+            assert_eq!(
+                Circuit::decode_signed(&Circuit::encode_signed(v)).unwrap(),
+                v
+            );
+        }
+        assert_eq!(Circuit::encode_signed(-1), -Fq::one());
+        assert_eq!(
+            Circuit::encode_signed(-5) + Circuit::encode_signed(3),
+            Circuit::encode_signed(-2)
+        );
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(values.len());
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let cipher =
+            Circuit::encrypt_signed(&pk, &values, &Randomness::rand(&mut rng), &params).unwrap();
+        assert_eq!(
+            Circuit::decrypt_signed(cipher, &sk, &params).unwrap(),
+            values
+        );
+
+        let beyond_max = Fq::from(i64::MAX as u64) + Fq::one();
+        let beyond_min = -Fq::from(1u64 << 63) - Fq::one();
+        let huge = Fq::from(u64::MAX) * Fq::from(u64::MAX);
+        for m in [beyond_max, beyond_min, huge, -huge] {
+            assert!(matches!(
+                Circuit::decode_signed(&m),
+                Err(EncryptionError::ValueOutOfRange)
+            ));
+        }
+        let params = Parameters::<JubJub>::default_multi(1);
+        let cipher =
+            Circuit::encrypt(&pk, &vec![huge], &Randomness::rand(&mut rng), &params).unwrap();
+        assert!(matches!(
+            Circuit::decrypt_signed(cipher, &sk, &params),
+            Err(EncryptionError::ValueOutOfRange)
+        ));
+    }
+
+    #[test]
+    fn fixed_length_circuits_prove_like_the_dynamic_ones() {
+        use crate::Bls12_381;
+        type CircuitN<const N: usize> = EncryptCircuitN<JubJub, JubJubVar, N>;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+
+        let fixed = CircuitN::<2>::new(
+            pk,
+            [Fq::from(3u64), Fq::from(4u64)],
+            params.clone(),
+            &mut rng,
+        )
+        .unwrap();
+        let proof = fixed.prove(&proving_key, &mut rng).unwrap();
+        let inputs =
+            CircuitN::<2>::get_public_inputs::<Bls12_381>(&fixed.resulted_ciphertext, &params);
+
+        let dynamic = Circuit::from(fixed.clone());
+        assert_eq!(
+            inputs,
+            Circuit::get_public_inputs::<Bls12_381>(&dynamic.resulted_ciphertext, &params)
+        );
+        assert!(Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+        assert!(Circuit::verify_proof(&vk, &dynamic.resulted_ciphertext, &proof, &params).unwrap());
+        assert_eq!(
+            Circuit::decrypt(dynamic.resulted_ciphertext.clone(), &sk, &params).unwrap(),
+            vec![Fq::from(3u64), Fq::from(4u64)]
+        );
+
+        let back = CircuitN::<2>::try_from(dynamic).unwrap();
+        assert_eq!(back.resulted_ciphertext, fixed.resulted_ciphertext);
+
+        assert!(matches!(
+            CircuitN::<3>::new(pk, [Fq::one(); 3], params.clone(), &mut rng),
+            Err(EncryptionError::LengthMismatch {
+                expected: 3,
+                got: 2
+            })
+        ));
+        let short = Circuit::new(pk, message(1), params.clone(), &mut rng).unwrap();
+        assert!(matches!(
+            CircuitN::<2>::try_from(short),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+        let with_aad =
+            Circuit::new_with_aad(pk, message(2), vec![Fq::one()], params, &mut rng).unwrap();
+        assert!(matches!(
+            CircuitN::<2>::try_from(with_aad),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+    }
+
+    #[test]
+    fn ciphertext_views_name_the_parts_and_convert_back() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let cipher =
+            Circuit::encrypt(&pk, &message(2), &Randomness::rand(&mut rng), &params).unwrap();
+
+        let view = CiphertextView::from(&cipher);
+        assert_eq!(*view.c1(), cipher.0);
+        assert_eq!(view.c2(), &cipher.1[..]);
+        assert_eq!(view.c2_at(1), Some(&cipher.1[1]));
+        assert_eq!(view.c2_at(2), None);
+        assert!(view.is_well_formed());
+        assert_eq!(Ciphertext::from(view), cipher);
+
+        let identity = (JubJub::zero(), cipher.1);
+        assert!(!CiphertextView::from(&identity).is_well_formed());
+    }
+
+    #[test]
+    fn checked_decryption_detects_the_wrong_key() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (other_sk, _) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(2);
+
+        let cipher =
+            Circuit::encrypt_checked(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+        assert_eq!(cipher.1.len(), 3);
+        assert_eq!(
+            Circuit::decrypt_checked(cipher.clone(), &sk, &params).unwrap(),
+            msg
+        );
+
+        let garbage = Circuit::decrypt(cipher.clone(), &other_sk, &params).unwrap();
+        assert_ne!(garbage[..2], msg[..]);
+        assert!(matches!(
+            Circuit::decrypt_checked(cipher.clone(), &other_sk, &params),
+            Err(EncryptionError::ChecksumMismatch)
+        ));
+        let mut tampered = cipher;
+        tampered.1[0] += Fq::one();
+        assert!(matches!(
+            Circuit::decrypt_checked(tampered, &sk, &params),
+            Err(EncryptionError::ChecksumMismatch)
+        ));
+
+        assert!(matches!(
+            Circuit::encrypt_checked(&pk, &message(3), &Randomness::rand(&mut rng), &params),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn committed_c1_matches_the_later_encryption() {
+        use ark_ed_on_bls12_381::Fr;
+
+        let mut rng = test_rng();
+        let custom = Parameters {
+            generator: JubJub::prime_subgroup_generator().mul(Fr::from(5u64).into_repr()),
+            ..Parameters::<JubJub>::default_multi(2)
+        };
+        for params in [Parameters::<JubJub>::default_multi(2), custom] {
+            let (_, pk) = Circuit::keygen_with_params(&params, &mut rng).unwrap();
+            let r = Randomness::rand(&mut rng);
+
+            let committed = Circuit::commit_c1(&r, &params);
+            assert_eq!(committed, params.generator.mul(r.0.into_repr()));
+            for msg in [message(1), message(2)] {
+                assert_eq!(
+                    Circuit::encrypt(&pk, &msg, &r, &params).unwrap().0,
+                    committed
+                );
+            }
+            assert_eq!(
+                Circuit::encrypt_affine(&pk, &message(2), &r, &params)
+                    .unwrap()
+                    .0,
+                committed.into_affine()
+            );
+            assert_ne!(
+                Circuit::commit_c1(&Randomness::rand(&mut rng), &params),
+                committed
+            );
+        }
+    }
+
+    #[test]
+    fn in_circuit_scalar_multiplication_is_complete_on_edge_cases() {
+        use ark_ed_on_bls12_381::Fr;
+
+        let mut rng = test_rng();
+        let g = JubJub::prime_subgroup_generator();
+        let r = Fr::rand(&mut rng);
+        for (point, scalar) in [
+            (g, Fr::zero()),
+            (JubJub::zero(), r),
+            (JubJub::zero(), Fr::zero()),
+            (g, r),
+        ] {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            let bits = Circuit::scalar_bits_var(cs.clone(), &scalar).unwrap();
+            let point_var = JubJubVar::new_witness(cs.clone(), || Ok(point)).unwrap();
+            let product = point_var.scalar_mul_le(bits.iter()).unwrap();
+            assert_eq!(product.value().unwrap(), point.mul(scalar.into_repr()));
+            assert!(cs.is_satisfied().unwrap());
+        }
+
+        // the circuit would accept these, so they are rejected before synthesis
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let zero = Randomness::from_scalar(Fr::zero());
+        assert!(matches!(
+            Circuit::encrypt(&pk, &message(1), &zero, &params),
+            Err(EncryptionError::ZeroRandomness)
+        ));
+        assert!(matches!(
+            Circuit::encrypt(
+                &JubJub::zero(),
+                &message(1),
+                &Randomness::rand(&mut rng),
+                &params
+            ),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        assert!(matches!(
+            Circuit::new(JubJub::zero(), message(1), params, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn tampered_c1_makes_a_ciphertext_ill_formed() {
+        use ark_ed_on_bls12_381::EdwardsAffine;
+        use ark_ff::SquareRootField;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let cipher =
+            Circuit::encrypt(&pk, &message(2), &Randomness::rand(&mut rng), &params).unwrap();
+        assert!(is_well_formed(&cipher));
+
+        let order_two: JubJub = EdwardsAffine::new(Fq::zero(), -Fq::one()).into();
+        let order_four: JubJub =
+            EdwardsAffine::new((-Fq::one()).sqrt().unwrap(), Fq::zero()).into();
+        let off_curve = EdwardsAffine::new(Fq::one(), Fq::one());
+        assert!(!off_curve.is_on_curve());
+        for c1 in [
+            JubJub::zero(),
+            off_curve.into(),
+            order_two,
+            order_four,
+            cipher.0 + order_two,
+            cipher.0 + order_four,
+        ] {
+            let tampered = (c1, cipher.1.clone());
+            assert!(!is_well_formed(&tampered));
+            assert!(!CiphertextView::from(&tampered).is_well_formed());
+        }
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn consuming_encryption_matches_encrypt() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let r = Randomness::rand(&mut rng);
+
+        for msg in [message(3), message(1), Vec::new()] {
+            let expected = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+            let cipher = Circuit::encrypt_consuming(&pk, msg.clone(), &r, &params).unwrap();
+            assert_eq!(cipher, expected);
+            assert_eq!(Circuit::decrypt(cipher, &sk, &params).unwrap(), msg);
+        }
+        assert!(matches!(
+            Circuit::encrypt_consuming(&JubJub::zero(), message(3), &r, &params),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn rotated_ciphertexts_decrypt_under_the_new_key() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(4);
+        let (old_sk, old_pk) = Circuit::keygen(&mut rng).unwrap();
+        let (new_sk, new_pk) = Circuit::keygen(&mut rng).unwrap();
+        let msgs = [message(4), message(2), Vec::new()];
+        let ciphers = msgs
+            .iter()
+            .map(|msg| Circuit::encrypt(&old_pk, msg, &Randomness::rand(&mut rng), &params))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let rotated = Circuit::rotate_key(&old_sk, &new_pk, &ciphers, &params, &mut rng).unwrap();
+        assert_eq!(rotated.len(), ciphers.len());
+        for ((cipher, old), msg) in rotated.into_iter().zip(&ciphers).zip(&msgs) {
+            assert_eq!(cipher.1.len(), old.1.len());
+            assert_ne!(cipher.0, old.0);
+            if !msg.is_empty() {
+                assert_ne!(
+                    &Circuit::decrypt(cipher.clone(), &old_sk, &params).unwrap(),
+                    msg
+                );
+            }
+            assert_eq!(&Circuit::decrypt(cipher, &new_sk, &params).unwrap(), msg);
+        }
+
+        assert!(matches!(
+            Circuit::rotate_key(&old_sk, &JubJub::zero(), &ciphers, &params, &mut rng),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_batch_verification_matches_a_serial_loop() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        // a handful of proofs cycled into a large batch keeps proving cheap
+        let proofs = (1..=4u64)
             .map(|i| {
-                FpVar::<C::BaseField>::new_witness(ns!(cs, "plaintext"), || {
-                    Ok(self.msg.get(i).map_or(C::BaseField::zero(), |c| c.clone()))
-                })
+                Circuit::encrypt_and_prove(&pk, &vec![Fq::from(i)], &params, &proving_key, &mut rng)
+                    .unwrap()
             })
-            .collect::<Result<_, _>>()?;
-        let ciphertext = self.ciphertext_var(cs.clone(), AllocationMode::Input)?;
+            .collect::<Vec<_>>();
+        let mut items = proofs.iter().cycle().take(100).cloned().collect::<Vec<_>>();
+        let serial = |items: &[(Ciphertext<JubJub>, Proof<Bls12_381>)]| {
+            items
+                .iter()
+                .all(|(cipher, proof)| Circuit::verify(&vk, cipher, proof, &params).unwrap())
+        };
+        assert!(Circuit::verify_batch_parallel(&vk, &items, &params).unwrap());
+
+        for i in [17, 58, 93] {
+            items[i].1 = items[i + 1].1.clone();
+        }
+        for batch in [
+            &items[..],
+            &items[..17],
+            &items[17..18],
+            &items[18..58],
+            &items[50..],
+        ] {
+            assert_eq!(
+                Circuit::verify_batch_parallel(&vk, batch, &params).unwrap(),
+                serial(batch)
+            );
+        }
+        assert!(!Circuit::verify_batch_parallel(&vk, &items, &params).unwrap());
+        assert!(Circuit::verify_batch_parallel(&vk, &items[..17], &params).unwrap());
+
+        let wider = Parameters::<JubJub>::default_multi(2);
+        assert!(Circuit::verify_batch_parallel(&vk, &items, &wider).is_err());
+    }
+
+    #[test]
+    fn accessors_read_back_the_inputs_of_new() {
+        let mut rng = test_rng();
+        let params = Parameters {
+            domain_tag: Some(Fq::from(9u64)),
+            ..Parameters::<JubJub>::default_multi(3)
+        };
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let circuit = Circuit::new(pk, message(2), params.clone(), &mut rng).unwrap();
+
+        assert_eq!(*circuit.public_key(), pk);
+        assert_eq!(circuit.plaintext(), &message(2));
+        assert_eq!(circuit.parameters().n, 3);
+        assert_eq!(circuit.parameters().compat_hash(), params.compat_hash());
+        assert!(!circuit.randomness().0.is_zero());
+        assert_eq!(
+            Circuit::encrypt(&pk, circuit.plaintext(), circuit.randomness(), &params).unwrap(),
+            circuit.resulted_ciphertext
+        );
+        assert_eq!(
+            Circuit::decrypt(
+                circuit.resulted_ciphertext.clone(),
+                &sk,
+                circuit.parameters()
+            )
+            .unwrap(),
+            *circuit.plaintext()
+        );
+    }
+
+    #[test]
+    fn encrypting_to_key_bytes_validates_the_key() {
+        use ark_ed_on_bls12_381::EdwardsAffine;
+        use ark_ff::SquareRootField;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let to_bytes = |point: EdwardsAffine| {
+            let mut bytes = Vec::new();
+            point.serialize(&mut bytes).unwrap();
+            bytes
+        };
+        let bytes = to_bytes(pk.into_affine());
+
+        let cipher = Circuit::encrypt_to_bytes(&bytes, &message(2), &params, &mut rng).unwrap();
+        assert_eq!(Circuit::decrypt(cipher, &sk, &params).unwrap(), message(2));
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        for malformed in [&trailing[..], &bytes[..bytes.len() - 1], &[], &[0xff; 32]] {
+            assert!(matches!(
+                Circuit::encrypt_to_bytes(malformed, &message(2), &params, &mut rng),
+                Err(EncryptionError::Serialization(_))
+            ));
+        }
+
+        // deserializing affine points already rejects those outside the prime-order subgroup
+        let order_four = EdwardsAffine::new((-Fq::one()).sqrt().unwrap(), Fq::zero());
+        assert!(matches!(
+            Circuit::encrypt_to_bytes(&to_bytes(order_four), &message(2), &params, &mut rng),
+            Err(EncryptionError::Serialization(_))
+        ));
+        assert!(matches!(
+            Circuit::encrypt_to_bytes(
+                &to_bytes(EdwardsAffine::zero()),
+                &message(2),
+                &params,
+                &mut rng
+            ),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn find_slot_returns_the_first_matching_slot() {
+        let mut rng = test_rng();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let target = Fq::from(1000u64);
+        let mut batch = message(16);
+        batch[11] = target;
+
+        for mask_derivation in [MaskDerivation::Indexed, MaskDerivation::Squeezed] {
+            let params = Parameters {
+                mask_derivation,
+                ..Parameters::<JubJub>::default_multi(16)
+            };
+            let cipher =
+                Circuit::encrypt(&pk, &batch, &Randomness::rand(&mut rng), &params).unwrap();
+            assert_eq!(
+                Circuit::find_slot(&cipher, &sk, &target, &params).unwrap(),
+                Some(11)
+            );
+            assert_eq!(
+                Circuit::find_slot(&cipher, &sk, &Fq::from(3u64), &params).unwrap(),
+                Some(2)
+            );
+            assert_eq!(
+                Circuit::find_slot(&cipher, &sk, &Fq::from(17u64), &params).unwrap(),
+                None
+            );
+            let (other_sk, _) = Circuit::keygen(&mut rng).unwrap();
+            assert_eq!(
+                Circuit::find_slot(&cipher, &other_sk, &target, &params).unwrap(),
+                None
+            );
+        }
+
+        let mut duplicated = message(4);
+        duplicated[3] = duplicated[1];
+        let params = Parameters::<JubJub>::default_multi(4);
+        let cipher =
+            Circuit::encrypt(&pk, &duplicated, &Randomness::rand(&mut rng), &params).unwrap();
+        assert_eq!(
+            Circuit::find_slot(&cipher, &sk, &duplicated[1], &params).unwrap(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn compat_hashes_are_stable_and_cover_every_setting() {
+        let params = Parameters::<JubJub>::default_multi(2);
+        assert_eq!(
+            params.compat_hash(),
+            Parameters::<JubJub>::default_multi(2).compat_hash()
+        );
+        assert_eq!(
+            Parameters::<JubJub>::default().compat_hash(),
+            Parameters::<JubJub>::default_multi(1).compat_hash()
+        );
+        // the digest is pinned so that a change of the serialization, which would break
+        // fingerprints exchanged between parties, is caught
+        assert_eq!(
+            hex::encode(params.compat_hash()),
+            "218f8c3d2e6f3b2c656cf868b277b9d001267659c96e604d38acd6db25aaffb9"
+        );
+
+        let changed = [
+            Parameters::<JubJub>::default_multi(3),
+            Parameters {
+                domain_tag: Some(Fq::one()),
+                ..params.clone()
+            },
+            Parameters {
+                bind_key: true,
+                ..params.clone()
+            },
+            Parameters {
+                mask_derivation: MaskDerivation::Squeezed,
+                ..params.clone()
+            },
+            Parameters {
+                generator: JubJub::prime_subgroup_generator().double(),
+                ..params.clone()
+            },
+            Parameters::<JubJub>::builder()
+                .n(2)
+                .poseidon_partial_rounds(60)
+                .build()
+                .unwrap(),
+        ];
+        for other in changed {
+            assert_ne!(other.compat_hash(), params.compat_hash());
+        }
+    }
+
+    #[test]
+    fn affine_ciphertexts_decrypt_like_projective_ones() {
+        let mut rng = test_rng();
+        for bind_key in [false, true] {
+            let params = Parameters {
+                bind_key,
+                ..Parameters::<JubJub>::default_multi(3)
+            };
+            let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+            let r = Randomness::rand(&mut rng);
 
-        self.verify_encryption(cs.clone(), &message, &ciphertext)
+            let cipher = Circuit::encrypt(&pk, &message(3), &r, &params).unwrap();
+            let (c1, c2) = Circuit::encrypt_affine(&pk, &message(3), &r, &params).unwrap();
+            assert_eq!(c1, cipher.0.into_affine());
+            assert_eq!(c2, cipher.1);
+            assert_eq!(
+                Circuit::decrypt_affine(c1, &c2, &sk, &params).unwrap(),
+                Circuit::decrypt(cipher, &sk, &params).unwrap()
+            );
+            assert_eq!(
+                Circuit::decrypt_affine(c1, &c2[..2], &sk, &params).unwrap(),
+                message(2)
+            );
+        }
+
+        let params = Parameters::<JubJub>::default_multi(1);
+        let r = Randomness::rand(&mut rng);
+        assert!(matches!(
+            Circuit::encrypt_affine(&JubJub::zero(), &message(1), &r, &params),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn boxed_rngs_build_circuits_like_generic_ones() {
+        use ark_std::boxed::Box;
+
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut test_rng()).unwrap();
+        let seed = [5u8; 32];
+
+        let mut boxed: Box<dyn RngCore> = Box::new(ChaCha20Rng::from_seed(seed));
+        let circuit = Circuit::new_dyn(pk, message(2), params.clone(), boxed.as_mut()).unwrap();
+        let generic = Circuit::new(
+            pk,
+            message(2),
+            params.clone(),
+            &mut ChaCha20Rng::from_seed(seed),
+        )
+        .unwrap();
+        assert_eq!(circuit.resulted_ciphertext, generic.resulted_ciphertext);
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let cipher = Circuit::encrypt_dyn(&pk, &message(2), &params, boxed.as_mut()).unwrap();
+        assert_eq!(Circuit::decrypt(cipher, &sk, &params).unwrap(), message(2));
+        assert!(matches!(
+            Circuit::new_dyn(JubJub::zero(), message(2), params.clone(), boxed.as_mut()),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        assert!(matches!(
+            Circuit::encrypt_dyn(&JubJub::zero(), &message(2), &params, boxed.as_mut()),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn deterministic_encryption_is_reproducible() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sender_sk, _) = Circuit::keygen(&mut rng).unwrap();
+        let (other_sender_sk, _) = Circuit::keygen(&mut rng).unwrap();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (_, other_pk) = Circuit::keygen(&mut rng).unwrap();
+
+        let cipher = Circuit::encrypt_deterministic(&pk, &sender_sk, &message(3), &params).unwrap();
+        assert_eq!(
+            Circuit::encrypt_deterministic(&pk, &sender_sk, &message(3), &params).unwrap(),
+            cipher
+        );
+        assert_eq!(
+            Circuit::decrypt(cipher.clone(), &sk, &params).unwrap(),
+            message(3)
+        );
+
+        let mut trailing_zero = message(2);
+        trailing_zero.push(Fq::zero());
+        let distinct = [
+            Circuit::encrypt_deterministic(&pk, &sender_sk, &message(2), &params).unwrap(),
+            Circuit::encrypt_deterministic(&pk, &sender_sk, &trailing_zero, &params).unwrap(),
+            Circuit::encrypt_deterministic(&pk, &other_sender_sk, &message(3), &params).unwrap(),
+            Circuit::encrypt_deterministic(&other_pk, &sender_sk, &message(3), &params).unwrap(),
+        ];
+        for other in &distinct {
+            assert_ne!(other.0, cipher.0);
+        }
+        assert_ne!(distinct[0].0, distinct[1].0);
+
+        assert!(matches!(
+            Circuit::encrypt_deterministic(&JubJub::zero(), &sender_sk, &message(3), &params),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+
+    #[test]
+    fn proofs_with_a_public_key_only_verify_for_that_key() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(1);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (_, other_pk) = Circuit::keygen(&mut rng).unwrap();
+        let circuit = Circuit::new(pk, vec![Fq::from(5u64)], params.clone(), &mut rng)
+            .unwrap()
+            .with_public_pk();
+        let cipher = circuit.resulted_ciphertext.clone();
+
+        let (proving_key, vk) =
+            Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+
+        let inputs = Circuit::get_public_inputs_with_pk::<Bls12_381>(&cipher, &pk, &params);
+        assert!(Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+        let inputs = Circuit::get_public_inputs_with_pk::<Bls12_381>(&cipher, &other_pk, &params);
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+    }
+
+    #[test]
+    fn cloned_circuits_synthesize_identically() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let circuit = Circuit::new(pk, vec![Fq::from(1u64), Fq::from(2u64)], params, &mut rng)
+            .unwrap()
+            .with_range_check(16)
+            .unwrap();
+
+        let synthesize = |circuit: Circuit| {
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            cs.finalize();
+            let cs = cs.into_inner().unwrap();
+            (
+                cs.to_matrices().unwrap(),
+                cs.instance_assignment,
+                cs.witness_assignment,
+            )
+        };
+        assert_eq!(synthesize(circuit.clone()), synthesize(circuit));
+    }
+
+    #[test]
+    fn proofs_under_one_domain_tag_fail_under_another() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let tagged = |tag: u64| {
+            Parameters::<JubJub>::builder()
+                .n(1)
+                .domain_tag(Fq::from(tag))
+                .build()
+                .unwrap()
+        };
+        let (params_a, params_b) = (tagged(1), tagged(2));
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(77u64)];
+
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params_a, &mut rng).unwrap();
+        let circuit = Circuit::new(pk, msg.clone(), params_a.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+
+        assert!(Circuit::verify(&vk, &cipher, &proof, &params_a).unwrap());
+        assert!(!Circuit::verify(&vk, &cipher, &proof, &params_b).unwrap());
+        assert_ne!(Circuit::decrypt(cipher, &sk, &params_b).unwrap(), msg);
+    }
+
+    #[test]
+    fn range_checks_accept_small_and_reject_large_plaintexts() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let ranged = |msg: Vec<Fq>, rng: &mut _| {
+            Circuit::new(pk, msg, params.clone(), rng)
+                .unwrap()
+                .with_range_check(8)
+                .unwrap()
+        };
+
+        let circuit = ranged(vec![Fq::from(0u64), Fq::from(255u64)], &mut rng);
+        let cipher = circuit.resulted_ciphertext.clone();
+        let (proving_key, vk) =
+            Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+        assert!(Circuit::verify(&vk, &cipher, &proof, &params).unwrap());
+
+        for out_of_range in [Fq::from(256u64), -Fq::one()] {
+            let circuit = ranged(vec![Fq::from(1u64), out_of_range], &mut rng);
+            let cs = ConstraintSystem::<Fq>::new_ref();
+            circuit.generate_constraints(cs.clone()).unwrap();
+            assert!(!cs.is_satisfied().unwrap());
+        }
+        assert!(ranged(vec![], &mut rng).with_range_check(255).is_err());
+    }
+
+    #[test]
+    fn proofs_of_a_public_plaintext_fail_for_another_plaintext() {
+        use crate::Bls12_381;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(31u64)];
+        let circuit = Circuit::new(pk, msg.clone(), params.clone(), &mut rng)
+            .unwrap()
+            .with_public_plaintext();
+        let cipher = circuit.resulted_ciphertext.clone();
+
+        let (proving_key, vk) =
+            Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+
+        let inputs = |msg: &Plaintext<JubJub>| {
+            Circuit::get_public_inputs_with_plaintext::<Bls12_381>(&cipher, msg, None, &params)
+        };
+        assert!(Groth16::<Bls12_381>::verify(&vk, &inputs(&msg), &proof).unwrap());
+        for claimed in [vec![Fq::from(32u64)], vec![msg[0], Fq::one()]] {
+            assert!(!Groth16::<Bls12_381>::verify(&vk, &inputs(&claimed), &proof).unwrap());
+        }
+    }
+
+    #[test]
+    fn chunked_masks_decrypt_and_satisfy_the_circuit() {
+        let mut rng = test_rng();
+        let chunked = Parameters::<JubJub>::builder()
+            .n(5)
+            .mask_derivation(MaskDerivation::Chunked(NonZeroUsize::new(3).unwrap()))
+            .build()
+            .unwrap();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(7u64); 5];
+
+        let circuit = Circuit::new(pk, msg.clone(), chunked.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+        assert_eq!(
+            Circuit::decrypt(cipher.clone(), &sk, &chunked).unwrap(),
+            msg
+        );
+        assert_eq!(
+            Circuit::find_slot(&cipher, &sk, &Fq::from(7u64), &chunked).unwrap(),
+            Some(0)
+        );
+        // slots within a chunk and across chunks get distinct masks
+        for i in 1..5 {
+            assert_ne!(cipher.1[0], cipher.1[i]);
+        }
+
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut bytes = Vec::new();
+        chunked.serialize(&mut bytes).unwrap();
+        let back = Parameters::<JubJub>::deserialize(&bytes[..]).unwrap();
+        assert_eq!(back.mask_derivation, chunked.mask_derivation);
+    }
+
+    #[test]
+    fn chunks_of_one_slot_match_indexed_masks() {
+        let mut rng = test_rng();
+        let indexed = Parameters::<JubJub>::default_multi(4);
+        let mut chunked = indexed.clone();
+        chunked.mask_derivation = MaskDerivation::Chunked(NonZeroUsize::new(1).unwrap());
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(4);
+        let r = Randomness::rand(&mut rng);
+
+        assert_eq!(
+            Circuit::encrypt(&pk, &msg, &r, &indexed).unwrap(),
+            Circuit::encrypt(&pk, &msg, &r, &chunked).unwrap()
+        );
+    }
+
+    // counts the permutations of the Poseidon gadget through the span `permute` opens
+    struct PermutationCounter(Arc<AtomicUsize>);
+
+    impl tracing::Subscriber for PermutationCounter {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            if span.metadata().name() == "permute" {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {}
+
+        fn enter(&self, _: &span::Id) {}
+
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn permutation_count_matches_the_permutations_of_the_sponge() {
+        let chunked = |k| MaskDerivation::Chunked(NonZeroUsize::new(k).unwrap());
+        let derivations = [
+            MaskDerivation::Indexed,
+            MaskDerivation::Squeezed,
+            chunked(2),
+            chunked(3),
+        ];
+        for mask_derivation in derivations {
+            for bind_key in [false, true] {
+                for domain_tag in [None, Some(Fq::from(5u64))] {
+                    let mut builder = Parameters::<JubJub>::builder()
+                        .bind_key(bind_key)
+                        .mask_derivation(mask_derivation);
+                    if let Some(tag) = domain_tag {
+                        builder = builder.domain_tag(tag);
+                    }
+                    let params = builder.build().unwrap();
+
+                    for msg_len in 1..=5 {
+                        let cs = ConstraintSystem::<Fq>::new_ref();
+                        let g = JubJubVar::new_witness(cs.clone(), || {
+                            Ok(JubJub::prime_subgroup_generator())
+                        })
+                        .unwrap();
+                        let kdf_inputs = domain_tag
+                            .map(|tag| FpVar::new_input(cs.clone(), || Ok(tag)).unwrap())
+                            .into_iter()
+                            .collect::<Vec<_>>();
+
+                        let permutations = Arc::new(AtomicUsize::new(0));
+                        let counter = PermutationCounter(permutations.clone());
+                        tracing::subscriber::with_default(counter, || {
+                            let sponge =
+                                Circuit::kdf_sponge_var(cs, &g, &g, &g, &kdf_inputs, &params)
+                                    .unwrap();
+                            Circuit::slot_masks_var(&sponge, msg_len, &params).unwrap();
+                        });
+                        assert_eq!(
+                            params.permutation_count(msg_len),
+                            permutations.load(Ordering::Relaxed),
+                            "{mask_derivation:?}, bind_key {bind_key}, tag {domain_tag:?}, {msg_len} slots"
+                        );
+                    }
+                }
+            }
+        }
     }
 }
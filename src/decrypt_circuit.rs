@@ -0,0 +1,218 @@
+use crate::circuit::{Ciphertext, EncryptCircuit, Parameters, Plaintext, PublicKey, SecretKey};
+use crate::sponge::{PoseidonHash, SpongeHash};
+use crate::EncryptionError;
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{PrimeField, ToConstraintField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::CurveVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::Absorb;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+/// Proves that a public plaintext is the decryption of a public ciphertext under the secret key
+/// of a public key, without revealing the secret key.
+///
+/// The key is tied to the public key through `pk = sk * g`, otherwise any secret key would do
+/// and the statement would hold for arbitrary plaintexts. Unlike [`EncryptCircuit`], which pads
+/// ciphertexts with zeros, this circuit expects `c2` to fill all `params.n` slots, since the
+/// plaintext of a padding slot isn't known to the verifier.
+#[derive(Clone)]
+pub struct DecryptCircuit<C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    sk: SecretKey<C>,
+    pk: PublicKey<C>,
+    cipher: Ciphertext<C>,
+    pub resulted_plaintext: Plaintext<C>,
+    params: Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, S> DecryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pub fn new(
+        cipher: Ciphertext<C>,
+        sk: SecretKey<C>,
+        params: Parameters<C, S>,
+    ) -> Result<Self, EncryptionError> {
+        if cipher.1.len() != params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: cipher.1.len(),
+            });
+        }
+
+        let mut pk = params.generator;
+        pk.mul_assign(sk.0);
+
+        let msg = EncryptCircuit::<C, CV, S>::decrypt(cipher.clone(), &sk, &params)?;
+
+        Ok(Self {
+            sk,
+            pk,
+            cipher,
+            resulted_plaintext: msg,
+            params,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Public inputs in allocation order: the public key, `c1`, `c2` and the plaintext.
+    pub fn get_public_inputs<E: PairingEngine>(
+        pk: &PublicKey<C>,
+        cipher: &Ciphertext<C>,
+        msg: &Plaintext<C>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let points = [pk, &cipher.0]
+            .into_iter()
+            .flat_map(|p| p.to_field_elements().unwrap());
+        let elements = cipher
+            .1
+            .iter()
+            .chain(msg.iter())
+            .flat_map(|f| f.to_field_elements().unwrap());
+        points.chain(elements).collect()
+    }
+}
+
+impl<C, CV, S> ConstraintSynthesizer<C::BaseField> for DecryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if self.cipher.1.len() != self.params.n || self.resulted_plaintext.len() != self.params.n {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let pk = CV::new_input(ns!(cs, "pub_key"), || Ok(self.pk))?;
+        let c1 = CV::new_input(ns!(cs, "ciphertext"), || Ok(self.cipher.0))?;
+        let c2 = Vec::<FpVar<C::BaseField>>::new_input(ns!(cs, "ciphertext"), || {
+            Ok(self.cipher.1.clone())
+        })?;
+        let plaintext = Vec::<FpVar<C::BaseField>>::new_input(ns!(cs, "plaintext"), || {
+            Ok(self.resulted_plaintext.clone())
+        })?;
+
+        let sk = EncryptCircuit::<C, CV, S>::scalar_bits_var(ns!(cs, "secret_key"), &self.sk.0)?;
+
+        // enforce pk = sk*generator
+        EncryptCircuit::<C, CV, S>::generator_mul_var(&sk, &self.params.generator)?
+            .enforce_equal(&pk)?;
+
+        // compute s = sk*c1
+        let s = c1.scalar_mul_le(sk.iter())?;
+
+        let kdf_inputs = EncryptCircuit::<C, CV, S>::constant_kdf_inputs(&self.params);
+        let sponge = EncryptCircuit::<C, CV, S>::kdf_sponge_var(
+            cs,
+            &s,
+            &pk,
+            &c1,
+            &kdf_inputs,
+            &self.params,
+        )?;
+
+        // enforce m_i = c2_i - dh_i
+        let masks = EncryptCircuit::<C, CV, S>::slot_masks_var(&sponge, c2.len(), &self.params)?;
+        plaintext
+            .iter()
+            .zip(c2.iter())
+            .zip(masks)
+            .try_for_each(|((m, c2i), dh)| (c2i - dh).enforce_equal(m))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::message;
+    use crate::circuit::Randomness;
+    use crate::{Bls12_381, JubJub, JubJubVar};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_groth16::Groth16;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_snark::SNARK;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn proves_and_verifies_a_decryption() {
+        type Circuit = DecryptCircuit<JubJub, JubJubVar>;
+        type Encrypt = EncryptCircuit<JubJub, JubJubVar>;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Encrypt::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(11u64), Fq::from(12u64)];
+        let cipher = Encrypt::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+
+        let circuit = Circuit::new(cipher.clone(), sk, params).unwrap();
+        assert_eq!(circuit.resulted_plaintext, msg);
+        let (proving_key, vk) =
+            Groth16::<Bls12_381>::circuit_specific_setup(circuit.clone(), &mut rng).unwrap();
+        let proof = Groth16::<Bls12_381>::prove(&proving_key, circuit, &mut rng).unwrap();
+
+        let inputs = Circuit::get_public_inputs::<Bls12_381>(&pk, &cipher, &msg);
+        assert!(Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+        let wrong = vec![msg[0], msg[1] + Fq::from(1u64)];
+        let inputs = Circuit::get_public_inputs::<Bls12_381>(&pk, &cipher, &wrong);
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+    }
+
+    #[test]
+    fn the_generator_is_a_constant_of_the_circuit() {
+        type Circuit = DecryptCircuit<JubJub, JubJubVar>;
+        type Encrypt = EncryptCircuit<JubJub, JubJubVar>;
+
+        // allocating the generator as a constant takes neither constraints nor variables
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let g = JubJubVar::new_constant(cs.clone(), JubJub::prime_subgroup_generator()).unwrap();
+        assert!(g.is_constant());
+        assert_eq!(cs.num_constraints(), 0);
+        assert_eq!(cs.num_witness_variables(), 0);
+        assert_eq!(cs.num_instance_variables(), 1);
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Encrypt::keygen(&mut rng).unwrap();
+        let cipher =
+            Encrypt::encrypt(&pk, &message(2), &Randomness::rand(&mut rng), &params).unwrap();
+        let circuit = Circuit::new(cipher, sk, params).unwrap();
+
+        // the secret key's multiple of the generator is checked against the public key
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        let mut wrong_key = circuit;
+        wrong_key.pk = -wrong_key.pk;
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        wrong_key.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
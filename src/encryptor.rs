@@ -0,0 +1,121 @@
+use crate::circuit::{Ciphertext, EncryptCircuit, Parameters, Plaintext, PublicKey, Randomness};
+use crate::sponge::{PoseidonHash, SpongeHash};
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_r1cs_std::groups::CurveVar;
+use ark_serialize::CanonicalSerialize;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::Absorb;
+use ark_std::collections::BTreeSet;
+use ark_std::marker::PhantomData;
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+
+/// Encrypts to a fixed public key while refusing to reuse randomness.
+///
+/// Two ciphertexts under the same `r` share their masks, so their difference reveals `m_a - m_b`.
+/// Since `c1 = r * g` determines `r`, every emitted `c1` is remembered and a ciphertext whose
+/// `c1` was seen before is rejected with `NonceReuse`. The record grows with every ciphertext
+/// and only covers this encryptor, it's a safety rail against faulty RNG state rather than a
+/// replacement for sampling fresh randomness.
+pub struct Encryptor<C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pk: PublicKey<C>,
+    params: Parameters<C, S>,
+    seen_c1: BTreeSet<Vec<u8>>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, S> Encryptor<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pub fn new(pk: PublicKey<C>, params: Parameters<C, S>) -> Result<Self, EncryptionError> {
+        EncryptCircuit::<C, CV, S>::validate_public_key(&pk)?;
+
+        Ok(Self {
+            pk,
+            params,
+            seen_c1: BTreeSet::new(),
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Encrypts `msg` under `r`, failing with `NonceReuse` if `r` was used before.
+    pub fn encrypt(
+        &mut self,
+        msg: &Plaintext<C>,
+        r: &Randomness<C>,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        let cipher = EncryptCircuit::<C, CV, S>::encrypt(&self.pk, msg, r, &self.params)?;
+
+        let mut c1 = Vec::new();
+        cipher
+            .0
+            .into_affine()
+            .serialize(&mut c1)
+            .expect("serializing into a vector can't fail");
+        if !self.seen_c1.insert(c1) {
+            return Err(EncryptionError::NonceReuse);
+        }
+        Ok(cipher)
+    }
+
+    /// Encrypts `msg` under randomness sampled from `rng`.
+    pub fn encrypt_rand<R: Rng>(
+        &mut self,
+        msg: &Plaintext<C>,
+        rng: &mut R,
+    ) -> Result<Ciphertext<C>, EncryptionError> {
+        self.encrypt(msg, &Randomness::rand(rng))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{JubJub, JubJubVar};
+    use ark_ff::Zero;
+    use ark_std::test_rng;
+
+    #[test]
+    fn reused_randomness_is_rejected() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let mut encryptor = Encryptor::<JubJub, JubJubVar>::new(pk, params.clone()).unwrap();
+        let r = Randomness::rand(&mut rng);
+
+        let cipher = encryptor.encrypt(&message(2), &r).unwrap();
+        assert_eq!(Circuit::decrypt(cipher, &sk, &params).unwrap(), message(2));
+        assert!(matches!(
+            encryptor.encrypt(&message(1), &r),
+            Err(EncryptionError::NonceReuse)
+        ));
+        // a ciphertext under another key doesn't share the masks
+        let (_, other_pk) = Circuit::keygen(&mut rng).unwrap();
+        let mut other = Encryptor::<JubJub, JubJubVar>::new(other_pk, params.clone()).unwrap();
+        assert!(other.encrypt(&message(1), &r).is_ok());
+
+        for _ in 0..4 {
+            assert!(encryptor.encrypt_rand(&message(2), &mut rng).is_ok());
+        }
+        assert!(matches!(
+            Encryptor::<JubJub, JubJubVar>::new(JubJub::zero(), params),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+}
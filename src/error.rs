@@ -0,0 +1,54 @@
+use ark_relations::r1cs::SynthesisError;
+use ark_serialize::SerializationError;
+use ark_std::string::String;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EncryptionError {
+    #[error("point is not on the curve or not in its prime-order subgroup")]
+    PointNotOnCurve,
+    #[error("point is the identity")]
+    IdentityPoint,
+    #[error("randomness is zero")]
+    ZeroRandomness,
+    #[error("length mismatch: expected {expected}, got {got}")]
+    LengthMismatch { expected: usize, got: usize },
+    #[error("ciphertexts were not produced under the same randomness")]
+    RandomnessMismatch,
+    #[error("randomness was already used for an earlier ciphertext")]
+    NonceReuse,
+    #[error("ciphertext authentication failed")]
+    AuthenticationFailed,
+    #[error("constraint synthesis failed: {0}")]
+    SynthesisFailed(SynthesisError),
+    #[error("serialization failed: {0}")]
+    Serialization(SerializationError),
+    #[error("unsupported artifact header: version {version}, kind {kind}")]
+    UnsupportedArtifact { version: u8, kind: u8 },
+    #[error("plaintext checksum mismatch, the secret key is likely wrong")]
+    ChecksumMismatch,
+    #[error("invalid length prefix or padding")]
+    InvalidPadding,
+    #[error("invalid hex encoding")]
+    InvalidHex,
+    #[error("decoded value is out of range")]
+    ValueOutOfRange,
+    #[error("invalid parameters: {0}")]
+    InvalidParameters(String),
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+// arkworks errors only implement `core::error::Error` with `std`, so these can't be `#[from]`
+impl From<SynthesisError> for EncryptionError {
+    fn from(err: SynthesisError) -> Self {
+        Self::SynthesisFailed(err)
+    }
+}
+
+impl From<SerializationError> for EncryptionError {
+    fn from(err: SerializationError) -> Self {
+        Self::Serialization(err)
+    }
+}
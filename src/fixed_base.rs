@@ -0,0 +1,96 @@
+use ark_ec::msm::FixedBaseMSM;
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// Windowed table of multiples of a base point, `C::prime_subgroup_generator()` unless built
+/// with [`PrecomputedGenerator::with_base`], trading memory for faster fixed-base scalar
+/// multiplications.
+#[derive(Clone, Debug)]
+pub struct PrecomputedGenerator<C: ProjectiveCurve> {
+    window: usize,
+    base: C,
+    table: Vec<Vec<C::Affine>>,
+}
+
+impl<C: ProjectiveCurve> PrecomputedGenerator<C> {
+    pub fn new(window: usize) -> Self {
+        Self::with_base(window, C::prime_subgroup_generator())
+    }
+
+    /// Builds the table for a custom generator, see [`crate::Parameters::with_generator`].
+    pub fn with_base(window: usize, base: C) -> Self {
+        let table = FixedBaseMSM::get_window_table(Self::scalar_size(), window, base);
+        Self {
+            window,
+            base,
+            table,
+        }
+    }
+
+    pub fn base(&self) -> &C {
+        &self.base
+    }
+
+    /// Computes `scalar * generator` from the table.
+    pub fn mul(&self, scalar: &C::ScalarField) -> C {
+        let outerc = Self::scalar_size().div_ceil(self.window);
+        FixedBaseMSM::windowed_mul(outerc, self.window, &self.table, scalar)
+    }
+
+    fn scalar_size() -> usize {
+        C::ScalarField::size_in_bits()
+    }
+}
+
+impl<C: ProjectiveCurve> Default for PrecomputedGenerator<C> {
+    fn default() -> Self {
+        Self::new(8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{EncryptionError, JubJub, Parameters, Randomness};
+    use ark_ed_on_bls12_381::Fr;
+    use ark_ff::{One, Zero};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn table_multiples_match_the_generator() {
+        let mut rng = test_rng();
+        for window in [1, 4, 8] {
+            let table = PrecomputedGenerator::<JubJub>::new(window);
+            let g = JubJub::prime_subgroup_generator();
+            assert!(table.mul(&Fr::zero()).is_zero());
+            assert_eq!(table.mul(&Fr::one()), g);
+            assert_eq!(table.mul(&-Fr::one()), -g);
+            for _ in 0..8 {
+                let r = Fr::rand(&mut rng);
+                assert_eq!(table.mul(&r), g.mul(r.into_repr()));
+            }
+        }
+    }
+
+    #[test]
+    fn encryption_with_a_table_yields_the_same_ciphertext() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(2);
+        let table = PrecomputedGenerator::default();
+
+        let r = Randomness::rand(&mut rng);
+        let cipher = Circuit::encrypt_with_table(&pk, &msg, &r, &params, &table).unwrap();
+        assert_eq!(cipher, Circuit::encrypt(&pk, &msg, &r, &params).unwrap());
+        assert_eq!(cipher.0, Circuit::commit_c1(&r, &params));
+
+        let other = PrecomputedGenerator::with_base(8, params.generator.double());
+        assert!(matches!(
+            Circuit::encrypt_with_table(&pk, &msg, &r, &params, &other),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+    }
+}
@@ -0,0 +1,113 @@
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::vec::Vec;
+
+// the Grain LFSR from the Poseidon reference implementation, used to derive the round
+// constants and MDS matrix for a given field and permutation configuration
+pub(crate) struct PoseidonGrainLFSR {
+    prime_num_bits: u64,
+    state: [bool; 80],
+    head: usize,
+}
+
+impl PoseidonGrainLFSR {
+    pub fn new(
+        prime_num_bits: u64,
+        state_len: u64,
+        num_full_rounds: u64,
+        num_partial_rounds: u64,
+    ) -> Self {
+        let mut state = [false; 80];
+
+        // b0, b1 describe the field (prime field)
+        state[1] = true;
+        // b2..b5 describe the S-box (x^alpha, not the inverse), so they all stay unset
+
+        // b6..b17 encode the number of bits of the prime
+        Self::write_bits(&mut state[6..18], prime_num_bits);
+        // b18..b29 encode the state width t = rate + capacity
+        Self::write_bits(&mut state[18..30], state_len);
+        // b30..b39 encode the number of full rounds
+        Self::write_bits(&mut state[30..40], num_full_rounds);
+        // b40..b49 encode the number of partial rounds
+        Self::write_bits(&mut state[40..50], num_partial_rounds);
+        // b50..b79 are all set
+        state[50..].iter_mut().for_each(|b| *b = true);
+
+        let mut lfsr = Self {
+            prime_num_bits,
+            state,
+            head: 0,
+        };
+        // discard the first 160 bits
+        (0..160).for_each(|_| {
+            lfsr.update();
+        });
+        lfsr
+    }
+
+    // samples field elements by rejecting candidates that overflow the modulus
+    pub fn get_field_elements_rejection_sampling<F: PrimeField>(
+        &mut self,
+        num_elems: usize,
+    ) -> Vec<F> {
+        (0..num_elems)
+            .map(|_| loop {
+                if let Some(f) = F::from_repr(F::BigInt::from_bits_le(&self.get_bits_le())) {
+                    break f;
+                }
+            })
+            .collect()
+    }
+
+    // samples field elements by reducing candidates modulo the prime
+    pub fn get_field_elements_mod_p<F: PrimeField>(&mut self, num_elems: usize) -> Vec<F> {
+        (0..num_elems)
+            .map(|_| {
+                let bytes = self
+                    .get_bits_le()
+                    .chunks(8)
+                    .map(|chunk| {
+                        chunk
+                            .iter()
+                            .enumerate()
+                            .fold(0u8, |byte, (i, &bit)| byte | (u8::from(bit) << i))
+                    })
+                    .collect::<Vec<_>>();
+                F::from_le_bytes_mod_order(&bytes)
+            })
+            .collect()
+    }
+
+    // the LFSR emits candidates most significant bit first
+    fn get_bits_le(&mut self) -> Vec<bool> {
+        let mut bits = (0..self.prime_num_bits)
+            .map(|_| self.next_bit())
+            .collect::<Vec<_>>();
+        bits.reverse();
+        bits
+    }
+
+    // self-shrinking: output the second bit of each pair whose first bit is set
+    fn next_bit(&mut self) -> bool {
+        while !self.update() {
+            self.update();
+        }
+        self.update()
+    }
+
+    fn update(&mut self) -> bool {
+        let new_bit = [62, 51, 38, 23, 13, 0].iter().fold(false, |acc, &offset| {
+            acc ^ self.state[(self.head + offset) % 80]
+        });
+        self.state[self.head] = new_bit;
+        self.head = (self.head + 1) % 80;
+        new_bit
+    }
+
+    fn write_bits(dest: &mut [bool], mut value: u64) {
+        dest.iter_mut().rev().for_each(|b| {
+            *b = value & 1 == 1;
+            value >>= 1;
+        });
+    }
+}
@@ -0,0 +1,61 @@
+//! Helpers specific to the default instantiation over JubJub.
+
+use crate::circuit::{Ciphertext, EncryptCircuit, Parameters};
+use crate::sponge::SpongeHash;
+use crate::{Bls12_381, JubJub};
+use ark_ec::PairingEngine;
+use ark_ed_on_bls12_381::Fq;
+use ark_r1cs_std::groups::CurveVar;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_std::vec::Vec;
+
+impl<CV, S> EncryptCircuit<JubJub, CV, S>
+where
+    CV: CurveVar<JubJub, Fq> + AbsorbGadget<Fq>,
+    S: SpongeHash<Fq>,
+{
+    /// [`EncryptCircuit::get_public_inputs`] for the default instantiation over JubJub, whose
+    /// circuits are proven with Groth16 over BLS12-381.
+    pub fn get_public_inputs_bls(
+        cipher: &Ciphertext<JubJub>,
+        params: &Parameters<JubJub, S>,
+    ) -> Vec<<Bls12_381 as PairingEngine>::Fr> {
+        Self::get_public_inputs::<Bls12_381>(cipher, params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{Bls12_381, Parameters};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_ff::One;
+    use ark_groth16::Groth16;
+    use ark_snark::SNARK;
+    use ark_std::test_rng;
+
+    #[test]
+    fn bls_public_inputs_verify_proofs() {
+        let mut rng = test_rng();
+        let params = Parameters {
+            domain_tag: Some(Fq::from(7u64)),
+            ..Parameters::default_multi(2)
+        };
+        let (_, pk) = Circuit::keygen(&mut rng).unwrap();
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let (cipher, proof) =
+            Circuit::encrypt_and_prove(&pk, &message(1), &params, &proving_key, &mut rng).unwrap();
+
+        let inputs = Circuit::get_public_inputs_bls(&cipher, &params);
+        assert_eq!(
+            inputs,
+            Circuit::get_public_inputs::<Bls12_381>(&cipher, &params)
+        );
+        assert!(Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+
+        let mut tampered = cipher;
+        tampered.1[0] += Fq::one();
+        let inputs = Circuit::get_public_inputs_bls(&tampered, &params);
+        assert!(!Groth16::<Bls12_381>::verify(&vk, &inputs, &proof).unwrap());
+    }
+}
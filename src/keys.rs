@@ -0,0 +1,137 @@
+use crate::circuit::{in_prime_order_subgroup, PublicKey, SecretKey};
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::string::String;
+use ark_std::vec::Vec;
+
+const HEX_PREFIX: &str = "0x";
+
+/// Encodes `pk` as `0x` followed by the hex of its compressed affine serialization.
+pub fn public_key_to_hex<C: ProjectiveCurve>(pk: &PublicKey<C>) -> String {
+    to_hex(&pk.into_affine())
+}
+
+/// Decodes a public key produced by [`public_key_to_hex`], failing with `PointNotOnCurve` on
+/// points that are off the curve or outside its prime-order subgroup and with `IdentityPoint`
+/// on the identity.
+pub fn public_key_from_hex<C: ProjectiveCurve>(s: &str) -> Result<PublicKey<C>, EncryptionError> {
+    // deserialization checks that the point is on the curve but not that it's in the subgroup
+    let pk = from_hex::<C::Affine>(s)?
+        .map(C::from)
+        .filter(in_prime_order_subgroup)
+        .ok_or(EncryptionError::PointNotOnCurve)?;
+    if pk.is_zero() {
+        return Err(EncryptionError::IdentityPoint);
+    }
+    Ok(pk)
+}
+
+/// Encodes `sk` as `0x` followed by the hex of its canonical little-endian serialization.
+pub fn secret_key_to_hex<C: ProjectiveCurve>(sk: &SecretKey<C>) -> String {
+    to_hex(&sk.0)
+}
+
+/// Decodes a secret key produced by [`secret_key_to_hex`], rejecting scalars that exceed the
+/// modulus.
+pub fn secret_key_from_hex<C: ProjectiveCurve>(s: &str) -> Result<SecretKey<C>, EncryptionError> {
+    from_hex::<C::ScalarField>(s)?
+        .map(SecretKey)
+        .ok_or(EncryptionError::InvalidHex)
+}
+
+fn to_hex<T: CanonicalSerialize>(value: &T) -> String {
+    let mut bytes = Vec::new();
+    value
+        .serialize(&mut bytes)
+        .expect("serializing into a vector can't fail");
+    let mut s = String::from(HEX_PREFIX);
+    s.push_str(&hex::encode(bytes));
+    s
+}
+
+// fails on malformed hex, while well-formed hex of a value that doesn't deserialize or leaves
+// trailing bytes yields `None`
+fn from_hex<T: CanonicalDeserialize>(s: &str) -> Result<Option<T>, EncryptionError> {
+    let bytes = s
+        .strip_prefix(HEX_PREFIX)
+        .and_then(|s| hex::decode(s).ok())
+        .ok_or(EncryptionError::InvalidHex)?;
+
+    let mut reader = &bytes[..];
+    let value = T::deserialize(&mut reader).ok();
+    Ok(value.filter(|_| reader.is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::Circuit;
+    use crate::JubJub;
+    use ark_ec::AffineCurve;
+    use ark_ed_on_bls12_381::{EdwardsAffine, Fq, Fr};
+    use ark_ff::{One, SquareRootField, Zero};
+    use ark_std::test_rng;
+
+    #[test]
+    fn keys_round_trip_through_hex() {
+        let (sk, pk) = Circuit::keygen(&mut test_rng()).unwrap();
+
+        let pk_hex = public_key_to_hex(&pk);
+        assert!(pk_hex.starts_with(HEX_PREFIX));
+        assert_eq!(pk_hex.len(), 2 + 64);
+        assert_eq!(public_key_from_hex::<JubJub>(&pk_hex).unwrap(), pk);
+
+        let sk_hex = secret_key_to_hex(&sk);
+        assert_eq!(secret_key_from_hex::<JubJub>(&sk_hex).unwrap().0, sk.0);
+    }
+
+    #[test]
+    fn malformed_hex_and_invalid_points_are_rejected() {
+        let (sk, pk) = Circuit::keygen(&mut test_rng()).unwrap();
+        let pk_hex = public_key_to_hex(&pk);
+        let sk_hex = secret_key_to_hex(&sk);
+
+        let malformed = [
+            pk_hex[2..].to_string(),
+            pk_hex[..pk_hex.len() - 1].to_string(),
+            format!("{}zz", &pk_hex[..pk_hex.len() - 2]),
+            "0x".to_string() + &"g".repeat(64),
+        ];
+        for s in &malformed {
+            assert!(matches!(
+                public_key_from_hex::<JubJub>(s),
+                Err(EncryptionError::InvalidHex)
+            ));
+        }
+        assert!(matches!(
+            secret_key_from_hex::<JubJub>(&sk_hex[..sk_hex.len() - 1]),
+            Err(EncryptionError::InvalidHex)
+        ));
+
+        // adding the point (sqrt(-1), 0) of order 4 leaves the prime-order subgroup
+        let order_four = EdwardsAffine::new((-Fq::one()).sqrt().unwrap(), Fq::zero());
+        assert!(order_four.is_on_curve());
+        for s in [
+            public_key_to_hex(&(pk + order_four.into_projective())),
+            "0x".to_string() + &"ff".repeat(32),
+            format!("{pk_hex}00"),
+        ] {
+            assert!(matches!(
+                public_key_from_hex::<JubJub>(&s),
+                Err(EncryptionError::PointNotOnCurve)
+            ));
+        }
+        assert!(matches!(
+            public_key_from_hex::<JubJub>(&public_key_to_hex(&JubJub::zero())),
+            Err(EncryptionError::IdentityPoint)
+        ));
+        // the scalar -1 with its top byte raised above the modulus
+        let mut too_large = to_hex(&-Fr::one());
+        too_large.replace_range(too_large.len() - 2.., "ff");
+        assert!(matches!(
+            secret_key_from_hex::<JubJub>(&too_large),
+            Err(EncryptionError::InvalidHex)
+        ));
+    }
+}
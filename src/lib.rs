@@ -1,8 +1,64 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod artifacts;
+#[cfg(feature = "bandersnatch")]
+pub mod bandersnatch;
+pub mod builder;
 pub mod circuit;
+pub mod decrypt_circuit;
+pub mod encryptor;
+pub mod error;
+pub mod fixed_base;
+mod grain_lfsr;
+mod jubjub;
+pub mod keys;
+pub mod multi_circuit;
 mod parameters;
+pub mod plaintext;
 pub mod poseidon;
+pub mod proxy;
+pub mod reencryption_circuit;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod sponge;
+pub mod stream;
+pub mod test_vectors;
+pub mod threshold;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+pub use crate::builder::{DecryptionBuilder, EncryptionBuilder};
 pub use crate::circuit::*;
+pub use crate::decrypt_circuit::DecryptCircuit;
+pub use crate::encryptor::Encryptor;
+pub use crate::error::EncryptionError;
+pub use crate::fixed_base::PrecomputedGenerator;
+pub use crate::keys::{
+    public_key_from_hex, public_key_to_hex, secret_key_from_hex, secret_key_to_hex,
+};
+pub use crate::multi_circuit::MultiEncryptCircuit;
+pub use crate::plaintext::{FromPlaintext, ToPlaintext};
+pub use crate::proxy::{ReEncryptedCiphertext, ReEncryptionKey};
+pub use crate::reencryption_circuit::ReEncryptionCircuit;
+#[cfg(feature = "serde")]
+pub use crate::serialization::SerializableCiphertext;
+pub use crate::sponge::{PoseidonHash, SpongeHash, WidePoseidonHash, WidePoseidonParameters};
+pub use crate::stream::{StreamDecryptor, StreamEncryptor};
 pub use ark_ed_on_bls12_381::{constraints::EdwardsVar as JubJubVar, EdwardsProjective as JubJub};
 
 pub use ark_bls12_381::Bls12_381;
+
+#[cfg(feature = "bn254")]
+pub use ark_bn254::Bn254;
+#[cfg(feature = "bn254")]
+pub use ark_ed_on_bn254::{
+    constraints::EdwardsVar as BabyJubJubVar, EdwardsProjective as BabyJubJub,
+};
+
+#[cfg(feature = "pasta")]
+pub use ark_pallas::{constraints::GVar as PallasVar, Projective as Pallas};
+#[cfg(feature = "pasta")]
+pub use ark_vesta::{constraints::GVar as VestaVar, Projective as Vesta};
+
+#[cfg(feature = "bandersnatch")]
+pub use crate::bandersnatch::{EdwardsProjective as Bandersnatch, EdwardsVar as BandersnatchVar};
@@ -0,0 +1,287 @@
+use crate::circuit::{
+    EncryptCircuit, MultiCiphertext, Parameters, Plaintext, PublicKey, Randomness,
+};
+use crate::sponge::{PoseidonHash, SpongeHash};
+use crate::EncryptionError;
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, ToConstraintField, Zero};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::CurveVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::Absorb;
+use ark_std::marker::PhantomData;
+use ark_std::rand::{CryptoRng, Rng};
+use ark_std::vec::Vec;
+use ark_std::{vec, UniformRand};
+
+/// Proves that a [`MultiCiphertext`] encrypts one message to every key in a public list, with
+/// the `c2` vector of each key derived from its own shared secret `r * pk_i` and all of them
+/// sharing `c1 = r * g`, see [`EncryptCircuit::encrypt_multi`].
+///
+/// Like [`crate::DecryptCircuit`], the circuit expects the message to fill all `params.n`
+/// slots. Its shape depends on the number of keys, so the setup has to be run for that number.
+#[derive(Clone)]
+pub struct MultiEncryptCircuit<C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    r: Randomness<C>,
+    msg: Plaintext<C>,
+    pks: Vec<PublicKey<C>>,
+    pub resulted_ciphertext: MultiCiphertext<C>,
+    params: Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, S> MultiEncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    /// Encrypts `msg` to every key in `pks` under one fresh randomness.
+    pub fn new<R: Rng>(
+        pks: Vec<PublicKey<C>>,
+        msg: Plaintext<C>,
+        params: Parameters<C, S>,
+        rnd: &mut R,
+    ) -> Result<Self, EncryptionError> {
+        Self::with_randomness(pks, msg, Randomness::rand(rnd), params)
+    }
+
+    fn with_randomness(
+        pks: Vec<PublicKey<C>>,
+        msg: Plaintext<C>,
+        r: Randomness<C>,
+        params: Parameters<C, S>,
+    ) -> Result<Self, EncryptionError> {
+        if msg.len() != params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: msg.len(),
+            });
+        }
+
+        let c1 = EncryptCircuit::<C, CV, S>::commit_c1(&r, &params);
+        let c2s = pks
+            .iter()
+            .map(|pk| EncryptCircuit::<C, CV, S>::encrypt(pk, &msg, &r, &params).map(|c| c.1))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            r,
+            msg,
+            pks,
+            resulted_ciphertext: (c1, c2s),
+            params,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Public inputs in allocation order: every public key, `c1` and the `c2` vector of every
+    /// key.
+    pub fn get_public_inputs<E: PairingEngine>(
+        pks: &[PublicKey<C>],
+        cipher: &MultiCiphertext<C>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let points = pks
+            .iter()
+            .chain([&cipher.0])
+            .flat_map(|p| p.to_field_elements().unwrap());
+        let elements = cipher
+            .1
+            .iter()
+            .flatten()
+            .flat_map(|f| f.to_field_elements().unwrap());
+        points.chain(elements).collect()
+    }
+
+    /// Runs the Groth16 setup for circuits encrypting to `num_keys` keys under `params`.
+    pub fn setup<E, R>(
+        num_keys: usize,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<(ProvingKey<E>, VerifyingKey<E>), EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        let blank = Self::with_randomness(
+            vec![params.generator; num_keys],
+            vec![C::BaseField::zero(); params.n],
+            Randomness(C::ScalarField::one()),
+            params.clone(),
+        )?;
+        Ok(Groth16::<E>::circuit_specific_setup(blank, rng)?)
+    }
+
+    pub fn prove<E, R>(&self, pk: &ProvingKey<E>, rng: &mut R) -> Result<Proof<E>, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        Ok(Groth16::<E>::prove(pk, self.clone(), rng)?)
+    }
+
+    pub fn verify<E>(
+        vk: &VerifyingKey<E>,
+        pks: &[PublicKey<C>],
+        cipher: &MultiCiphertext<C>,
+        proof: &Proof<E>,
+    ) -> Result<bool, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let public_inputs = Self::get_public_inputs::<E>(pks, cipher);
+        Ok(Groth16::<E>::verify(vk, &public_inputs, proof)?)
+    }
+}
+
+impl<C, CV, S> ConstraintSynthesizer<C::BaseField> for MultiEncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        let (c1, c2s) = &self.resulted_ciphertext;
+        if self.msg.len() != self.params.n
+            || c2s.len() != self.pks.len()
+            || c2s.iter().any(|c2| c2.len() != self.params.n)
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let pks = self
+            .pks
+            .iter()
+            .map(|pk| CV::new_input(ns!(cs, "pub_key"), || Ok(*pk)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let c1_var = CV::new_input(ns!(cs, "ciphertext"), || Ok(*c1))?;
+        let c2s = c2s
+            .iter()
+            .map(|c2| {
+                Vec::<FpVar<C::BaseField>>::new_input(ns!(cs, "ciphertext"), || Ok(c2.as_slice()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let message =
+            Vec::<FpVar<C::BaseField>>::new_witness(ns!(cs, "plaintext"), || Ok(self.msg.clone()))?;
+
+        let randomness =
+            EncryptCircuit::<C, CV, S>::scalar_bits_var(ns!(cs, "randomness"), &self.r.0)?;
+
+        // enforce c1 = randomness*generator once for all keys
+        EncryptCircuit::<C, CV, S>::generator_mul_var(&randomness, &self.params.generator)?
+            .enforce_equal(&c1_var)?;
+
+        // enforce c2_i = m_i + dh_i under the shared secret s = randomness*pk of every key
+        let kdf_inputs = EncryptCircuit::<C, CV, S>::constant_kdf_inputs(&self.params);
+        pks.iter().zip(c2s.iter()).try_for_each(|(pk, c2)| {
+            let s = pk.scalar_mul_le(randomness.iter())?;
+            let sponge = EncryptCircuit::<C, CV, S>::kdf_sponge_var(
+                cs.clone(),
+                &s,
+                pk,
+                &c1_var,
+                &kdf_inputs,
+                &self.params,
+            )?;
+
+            let masks =
+                EncryptCircuit::<C, CV, S>::slot_masks_var(&sponge, c2.len(), &self.params)?;
+            message
+                .iter()
+                .zip(c2.iter())
+                .zip(masks)
+                .try_for_each(|((m, c2i), dh)| (m + dh).enforce_equal(c2i))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{Bls12_381, JubJub, JubJubVar};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type Multi = MultiEncryptCircuit<JubJub, JubJubVar>;
+
+    #[test]
+    fn one_proof_covers_both_recipients() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let keys = (0..2)
+            .map(|_| Circuit::keygen(&mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let pks = keys.iter().map(|(_, pk)| *pk).collect::<Vec<_>>();
+        let (proving_key, vk) = Multi::setup::<Bls12_381, _>(2, &params, &mut rng).unwrap();
+
+        let circuit = Multi::new(pks.clone(), message(2), params.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+        for ((sk, _), c2) in keys.iter().zip(&cipher.1) {
+            assert_eq!(
+                Circuit::decrypt((cipher.0, c2.clone()), sk, &params).unwrap(),
+                message(2)
+            );
+        }
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+        assert!(Multi::verify(&vk, &pks, &cipher, &proof).unwrap());
+
+        let mut tampered = cipher.clone();
+        tampered.1[1][0] += Fq::one();
+        assert!(!Multi::verify(&vk, &pks, &tampered, &proof).unwrap());
+        let swapped = [pks[1], pks[0]];
+        assert!(!Multi::verify(&vk, &swapped, &cipher, &proof).unwrap());
+    }
+
+    #[test]
+    fn tampered_slots_and_short_messages_are_rejected() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let pks = (0..2)
+            .map(|_| Circuit::keygen(&mut rng).unwrap().1)
+            .collect::<Vec<_>>();
+
+        let mut circuit = Multi::new(pks.clone(), message(2), params.clone(), &mut rng).unwrap();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        circuit.resulted_ciphertext.1[0][1] += Fq::one();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+
+        assert!(matches!(
+            Multi::new(pks, message(1), params, &mut rng),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 1
+            })
+        ));
+    }
+}
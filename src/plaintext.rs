@@ -0,0 +1,165 @@
+//! Conversions between application types and the field elements they are encrypted as.
+//!
+//! Implementations are provided for plaintexts themselves, arrays and tuples of up to four
+//! base field elements. Records implement the traits by flattening their fields in a fixed
+//! order, e.g. through the implementation for arrays.
+//!
+//! As the base field doesn't determine the curve, converting arrays and tuples may need the
+//! curve spelled out, e.g. `ToPlaintext::<JubJub>::to_plaintext(&pair)`.
+
+use crate::circuit::Plaintext;
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_std::vec::Vec;
+
+/// Types that can be flattened into the slots of a plaintext.
+pub trait ToPlaintext<C: ProjectiveCurve> {
+    fn to_plaintext(&self) -> Plaintext<C>;
+}
+
+/// Types that can be recovered from the slots produced by their [`ToPlaintext`]
+/// implementation, failing with `LengthMismatch` on a wrong number of slots.
+pub trait FromPlaintext<C: ProjectiveCurve>: Sized {
+    fn from_plaintext(msg: &[C::BaseField]) -> Result<Self, EncryptionError>;
+}
+
+impl<C: ProjectiveCurve> ToPlaintext<C> for Vec<C::BaseField> {
+    fn to_plaintext(&self) -> Plaintext<C> {
+        self.clone()
+    }
+}
+
+impl<C: ProjectiveCurve> FromPlaintext<C> for Vec<C::BaseField> {
+    fn from_plaintext(msg: &[C::BaseField]) -> Result<Self, EncryptionError> {
+        Ok(msg.to_vec())
+    }
+}
+
+impl<C: ProjectiveCurve, const N: usize> ToPlaintext<C> for [C::BaseField; N] {
+    fn to_plaintext(&self) -> Plaintext<C> {
+        self.to_vec()
+    }
+}
+
+impl<C: ProjectiveCurve, const N: usize> FromPlaintext<C> for [C::BaseField; N] {
+    fn from_plaintext(msg: &[C::BaseField]) -> Result<Self, EncryptionError> {
+        msg.try_into().map_err(|_| EncryptionError::LengthMismatch {
+            expected: N,
+            got: msg.len(),
+        })
+    }
+}
+
+// every tuple element is a base field element, `$slot` only drives the repetition
+macro_rules! base_field {
+    ($slot:ident, $curve:ident) => {
+        <$curve as ProjectiveCurve>::BaseField
+    };
+}
+
+macro_rules! impl_for_tuple {
+    ($len:literal: $($slot:ident),+) => {
+        impl<C: ProjectiveCurve> ToPlaintext<C> for ($(base_field!($slot, C),)+) {
+            fn to_plaintext(&self) -> Plaintext<C> {
+                let ($($slot,)+) = *self;
+                ark_std::vec![$($slot),+]
+            }
+        }
+
+        impl<C: ProjectiveCurve> FromPlaintext<C> for ($(base_field!($slot, C),)+) {
+            fn from_plaintext(msg: &[C::BaseField]) -> Result<Self, EncryptionError> {
+                let [$($slot),+] =
+                    <[C::BaseField; $len] as FromPlaintext<C>>::from_plaintext(msg)?;
+                Ok(($($slot,)+))
+            }
+        }
+    };
+}
+
+impl_for_tuple!(1: a);
+impl_for_tuple!(2: a, b);
+impl_for_tuple!(3: a, b, c);
+impl_for_tuple!(4: a, b, c, d);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::Circuit;
+    use crate::{JubJub, Parameters, Randomness};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_std::{test_rng, vec, UniformRand};
+
+    #[derive(Debug, PartialEq)]
+    struct Transfer {
+        amount: Fq,
+        nonce: Fq,
+        recipient: [Fq; 2],
+    }
+
+    impl ToPlaintext<JubJub> for Transfer {
+        fn to_plaintext(&self) -> Plaintext<JubJub> {
+            let mut msg = vec![self.amount, self.nonce];
+            msg.extend(ToPlaintext::<JubJub>::to_plaintext(&self.recipient));
+            msg
+        }
+    }
+
+    impl FromPlaintext<JubJub> for Transfer {
+        fn from_plaintext(msg: &[Fq]) -> Result<Self, EncryptionError> {
+            let [amount, nonce, r0, r1] = <[Fq; 4] as FromPlaintext<JubJub>>::from_plaintext(msg)?;
+            Ok(Self {
+                amount,
+                nonce,
+                recipient: [r0, r1],
+            })
+        }
+    }
+
+    #[test]
+    fn records_round_trip_through_encryption() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(4);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let transfer = Transfer {
+            amount: Fq::from(250u64),
+            nonce: Fq::rand(&mut rng),
+            recipient: [Fq::rand(&mut rng), Fq::rand(&mut rng)],
+        };
+
+        let cipher = Circuit::encrypt(
+            &pk,
+            &transfer.to_plaintext(),
+            &Randomness::rand(&mut rng),
+            &params,
+        )
+        .unwrap();
+        let decrypted = Circuit::decrypt(cipher, &sk, &params).unwrap();
+        assert_eq!(Transfer::from_plaintext(&decrypted).unwrap(), transfer);
+    }
+
+    #[test]
+    fn tuples_and_arrays_check_the_number_of_slots() {
+        let (a, b, c) = (Fq::from(1u64), Fq::from(2u64), Fq::from(3u64));
+        let triple = ToPlaintext::<JubJub>::to_plaintext(&(a, b, c));
+        assert_eq!(triple, vec![a, b, c]);
+        assert_eq!(
+            <(Fq, Fq, Fq) as FromPlaintext<JubJub>>::from_plaintext(&triple).unwrap(),
+            (a, b, c)
+        );
+
+        assert!(matches!(
+            <(Fq, Fq) as FromPlaintext<JubJub>>::from_plaintext(&triple),
+            Err(EncryptionError::LengthMismatch {
+                expected: 2,
+                got: 3
+            })
+        ));
+        assert!(matches!(
+            Transfer::from_plaintext(&triple),
+            Err(EncryptionError::LengthMismatch {
+                expected: 4,
+                got: 3
+            })
+        ));
+    }
+}
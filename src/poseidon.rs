@@ -1,11 +1,25 @@
+use crate::grain_lfsr::PoseidonGrainLFSR;
 use crate::parameters::*;
+use crate::sponge::WidePoseidonParameters;
+use crate::EncryptionError;
 use ark_ec::ProjectiveCurve;
 use ark_ff::PrimeField;
+use ark_relations::r1cs::ConstraintSystem;
+use ark_sponge::constraints::CryptographicSpongeVar;
+use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
 use ark_sponge::poseidon::PoseidonParameters;
-use ark_sponge::Absorb;
-use std::fmt::Debug;
-use std::marker::PhantomData;
-use std::str::FromStr;
+use ark_std::fmt::Debug;
+use ark_std::format;
+use ark_std::str::FromStr;
+use ark_std::vec::Vec;
+
+#[cfg(feature = "serde")]
+use crate::serialization::field_to_decimal;
+#[cfg(feature = "serde")]
+use ark_std::string::String;
+
+/// The only rate supported by `ark-sponge`'s Poseidon sponge, whose state width is fixed to 3.
+pub const POSEIDON_RATE: usize = 2;
 
 // returns optimized for constraints
 pub fn get_poseidon_params<C: ProjectiveCurve>(_rate: usize) -> PoseidonParameters<C::BaseField>
@@ -13,27 +27,568 @@ where
     C::BaseField: PrimeField,
     <C::BaseField as FromStr>::Err: Debug,
 {
-    let arks = P1["ark"]
-        .members()
-        .map(|ark| {
-            ark.members()
-                .map(|v| C::BaseField::from_str(v.as_str().unwrap()).unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
-    let mds = P1["mds"]
-        .members()
-        .map(|m| {
-            m.members()
-                .map(|v| C::BaseField::from_str(v.as_str().unwrap()).unwrap())
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
+    #[cfg(feature = "bn254")]
+    if is_bn254_scalar_field::<C::BaseField>() {
+        return get_bn254_poseidon_params::<C::BaseField>();
+    }
+
+    #[cfg(feature = "pasta")]
+    if is_pasta_field::<C::BaseField>() {
+        return get_pasta_poseidon_params::<C::BaseField>();
+    }
+
+    let parse = |rows: &[[&str; 3]]| {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|v| C::BaseField::from_str(v).unwrap())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>()
+    };
     PoseidonParameters::new(
-        P1["full_rounds"].as_u32().unwrap(),
-        P1["partial_rounds"].as_u32().unwrap(),
-        P1["alpha"].as_u64().unwrap(),
+        P1_FULL_ROUNDS,
+        P1_PARTIAL_ROUNDS,
+        P1_ALPHA,
+        parse(&P1_MDS),
+        parse(&P1_ARK),
+    )
+}
+
+/// Poseidon parameters over the base field of `C` for `security_bits` of security, either 128,
+/// the level of [`get_poseidon_params`], or 256.
+///
+/// The 256-bit configuration keeps the S-box of the 128-bit one, with 8 full rounds (10 over
+/// fields too small for 6 to withstand statistical attacks) and the partial rounds needed
+/// against interpolation attacks plus the 7.5% margin of the Poseidon paper. This strengthens
+/// the permutation only: with a capacity of one field element, the generic security of the
+/// sponge stays bounded by the size of the field.
+pub fn get_poseidon_params_for<C: ProjectiveCurve>(
+    rate: usize,
+    security_bits: usize,
+) -> Result<PoseidonParameters<C::BaseField>, EncryptionError>
+where
+    C::BaseField: PrimeField,
+    <C::BaseField as FromStr>::Err: Debug,
+{
+    if rate != POSEIDON_RATE {
+        return Err(EncryptionError::InvalidParameters(format!(
+            "unsupported Poseidon rate {rate}, only {POSEIDON_RATE} is supported"
+        )));
+    }
+
+    let params = get_poseidon_params::<C>(rate);
+    match security_bits {
+        128 => Ok(params),
+        256 => {
+            let field_bits = C::BaseField::size_in_bits();
+            let width = rate + 1;
+            let (_, _, alpha, _, _) = poseidon_params_parts(&params);
+
+            let statistical_bound = (field_bits - 1 - ceil_log(2, u128::from(alpha - 1))) * width;
+            let full_rounds = if security_bits <= statistical_bound {
+                6
+            } else {
+                10
+            };
+            let min_rounds = ceil_log_pow2(alpha, security_bits.min(field_bits))
+                + ceil_log(alpha, width as u128);
+            let partial_rounds = (min_rounds.saturating_sub(full_rounds) * 1075).div_ceil(1000);
+
+            generate_poseidon_params(rate, full_rounds + 2, partial_rounds, alpha)
+        }
+        _ => Err(EncryptionError::InvalidParameters(format!(
+            "unsupported security level of {security_bits} bits, expected 128 or 256"
+        ))),
+    }
+}
+
+/// Poseidon parameters over the base field of `C` at any non-zero rate, for 128 bits of security.
+///
+/// Rate 2 takes the parameters of [`get_poseidon_params`]. Other rates keep their S-box and
+/// full rounds, with the partial rounds needed against interpolation attacks on the wider state
+/// plus the 7.5% margin of the Poseidon paper, but never fewer than at rate 2.
+pub fn get_wide_poseidon_params<C: ProjectiveCurve>(
+    rate: usize,
+) -> Result<WidePoseidonParameters<C::BaseField>, EncryptionError>
+where
+    C::BaseField: PrimeField,
+    <C::BaseField as FromStr>::Err: Debug,
+{
+    let default = get_poseidon_params::<C>(POSEIDON_RATE);
+    if rate == POSEIDON_RATE {
+        return Ok((&default).into());
+    }
+
+    let (full_rounds, partial_rounds, alpha, _, _) = poseidon_params_parts(&default);
+    let (full_rounds, partial_rounds) = (full_rounds as usize, partial_rounds as usize);
+    let security_bits = (POSEIDON_SECURITY_BITS as usize).min(C::BaseField::size_in_bits());
+    let min_rounds =
+        ceil_log_pow2(alpha, security_bits) + ceil_log(alpha, rate.saturating_add(1) as u128);
+    let wide_partial_rounds = (min_rounds.saturating_sub(full_rounds) * 1075).div_ceil(1000);
+
+    WidePoseidonParameters::new(
+        rate,
+        full_rounds,
+        partial_rounds.max(wide_partial_rounds),
+        alpha,
+    )
+}
+
+// derives the round constants and MDS matrix for the given configuration with the Grain LFSR,
+// following the Poseidon reference implementation
+pub fn find_poseidon_ark_and_mds<F: PrimeField>(
+    rate: usize,
+    full_rounds: u64,
+    partial_rounds: u64,
+    skip_matrices: u64,
+) -> (Vec<Vec<F>>, Vec<Vec<F>>) {
+    let width = rate + 1;
+    let mut lfsr = PoseidonGrainLFSR::new(
+        F::size_in_bits() as u64,
+        width as u64,
+        full_rounds,
+        partial_rounds,
+    );
+
+    let ark = (0..full_rounds + partial_rounds)
+        .map(|_| lfsr.get_field_elements_rejection_sampling(width))
+        .collect();
+
+    (0..skip_matrices).for_each(|_| {
+        lfsr.get_field_elements_mod_p::<F>(2 * width);
+    });
+
+    // Cauchy matrix mds[i][j] = 1 / (x_i + y_j)
+    let xs = lfsr.get_field_elements_mod_p::<F>(width);
+    let ys = lfsr.get_field_elements_mod_p::<F>(width);
+    let mds = xs
+        .iter()
+        .map(|x| {
+            ys.iter()
+                .map(|y| (*x + y).inverse().expect("x_i + y_j must be non-zero"))
+                .collect()
+        })
+        .collect();
+
+    (ark, mds)
+}
+
+#[cfg(feature = "bn254")]
+fn is_bn254_scalar_field<F: PrimeField>() -> bool {
+    F::characteristic() == <ark_bn254::Fr as ark_ff::Field>::characteristic()
+}
+
+// x^5 S-box with the round numbers recommended for 128-bit security over 254-bit fields
+#[cfg(feature = "bn254")]
+fn get_bn254_poseidon_params<F: PrimeField>() -> PoseidonParameters<F> {
+    let (full_rounds, partial_rounds) = (8, 57);
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(2, full_rounds, partial_rounds, 0);
+    PoseidonParameters::new(full_rounds as u32, partial_rounds as u32, 5, mds, ark)
+}
+
+// the base fields of Pallas and Vesta, each being the scalar field of the other
+#[cfg(feature = "pasta")]
+fn is_pasta_field<F: PrimeField>() -> bool {
+    F::characteristic() == <ark_pallas::Fq as ark_ff::Field>::characteristic()
+        || F::characteristic() == <ark_vesta::Fq as ark_ff::Field>::characteristic()
+}
+
+// x^5 S-box with the round numbers of the Pasta Poseidon instance used by Halo 2
+#[cfg(feature = "pasta")]
+fn get_pasta_poseidon_params<F: PrimeField>() -> PoseidonParameters<F> {
+    let (full_rounds, partial_rounds) = (8, 56);
+    let (ark, mds) = find_poseidon_ark_and_mds::<F>(2, full_rounds, partial_rounds, 0);
+    PoseidonParameters::new(full_rounds as u32, partial_rounds as u32, 5, mds, ark)
+}
+
+/// Security level the Poseidon configuration is checked against, in bits.
+pub const POSEIDON_SECURITY_BITS: u32 = 128;
+
+/// Constructs Poseidon parameters over `F` with constants derived via the Grain LFSR, after
+/// checking the configuration with [`validate_poseidon_config`]. The rate has to be the one
+/// supported by `ark-sponge`, see [`crate::sponge::WidePoseidonParameters`] for other rates.
+pub fn generate_poseidon_params<F: PrimeField>(
+    rate: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+) -> Result<PoseidonParameters<F>, EncryptionError> {
+    if rate != POSEIDON_RATE {
+        return Err(EncryptionError::InvalidParameters(format!(
+            "unsupported Poseidon rate {rate}, only {POSEIDON_RATE} is supported"
+        )));
+    }
+    validate_poseidon_config::<F>(rate, full_rounds, partial_rounds, alpha)?;
+
+    let (ark, mds) =
+        find_poseidon_ark_and_mds::<F>(rate, full_rounds as u64, partial_rounds as u64, 0);
+    Ok(PoseidonParameters::new(
+        full_rounds as u32,
+        partial_rounds as u32,
+        alpha,
         mds,
-        arks,
+        ark,
+    ))
+}
+
+/// Checks a Poseidon configuration over `F`: the rate has to be non-zero, `x^alpha` has to be a
+/// permutation of `F`, the full rounds have to withstand statistical attacks and the total
+/// number of rounds has to exceed the interpolation bound for [`POSEIDON_SECURITY_BITS`] of
+/// security.
+pub fn validate_poseidon_config<F: PrimeField>(
+    rate: usize,
+    full_rounds: usize,
+    partial_rounds: usize,
+    alpha: u64,
+) -> Result<(), EncryptionError> {
+    if rate == 0 {
+        return Err(EncryptionError::InvalidParameters(
+            "a Poseidon rate of at least 1 is required".into(),
+        ));
+    }
+    if alpha < 3 || !is_permutation_exponent::<F>(alpha) {
+        return Err(EncryptionError::InvalidParameters(format!(
+            "x^{alpha} is not a permutation of the field"
+        )));
+    }
+    if full_rounds < 6 || !full_rounds.is_multiple_of(2) {
+        return Err(EncryptionError::InvalidParameters(format!(
+            "{full_rounds} full rounds, expected an even number of at least 6"
+        )));
+    }
+
+    // 2^128 doesn't fit a u128, but a power of alpha overflowing it exceeds 2^128 all the same
+    let security_bound = 1u128
+        .checked_shl(POSEIDON_SECURITY_BITS)
+        .unwrap_or(u128::MAX);
+    let min_rounds = ceil_log(alpha, security_bound) + ceil_log(alpha, (rate + 1) as u128);
+    if full_rounds + partial_rounds < min_rounds {
+        return Err(EncryptionError::InvalidParameters(format!(
+            "{} rounds in total, at least {min_rounds} are required against interpolation attacks",
+            full_rounds + partial_rounds
+        )));
+    }
+    Ok(())
+}
+
+// smallest k with alpha^k >= target, where powers overflowing a u128 count as exceeding it
+fn ceil_log(alpha: u64, target: u128) -> usize {
+    let mut power = Some(1u128);
+    let mut k = 0;
+    while let Some(p) = power.filter(|&p| p < target) {
+        power = p.checked_mul(u128::from(alpha));
+        k += 1;
+    }
+    k
+}
+
+// smallest k with alpha^k >= 2^bits, tracking alpha^k as a u128 mantissa that is truncated
+// whenever it grows large, which can only overestimate k
+fn ceil_log_pow2(alpha: u64, bits: usize) -> usize {
+    let (mut mantissa, mut exponent, mut k) = (1u128, 0, 0);
+    while exponent + (127 - mantissa.leading_zeros() as usize) < bits {
+        mantissa *= u128::from(alpha);
+        if mantissa >> 64 != 0 {
+            mantissa >>= 32;
+            exponent += 32;
+        }
+        k += 1;
+    }
+    k
+}
+
+/// Smallest exponent `alpha >= 3` for which `x^alpha` is a permutation of `F`.
+pub fn default_alpha<F: PrimeField>() -> u64 {
+    (3..)
+        .find(|&alpha| is_permutation_exponent::<F>(alpha))
+        .expect("some small exponent is coprime to p - 1")
+}
+
+// x^alpha permutes F iff gcd(alpha, p - 1) = 1
+fn is_permutation_exponent<F: PrimeField>(alpha: u64) -> bool {
+    let p_mod_alpha = F::characteristic().iter().rev().fold(0u128, |rem, &limb| {
+        ((rem << 64) | u128::from(limb)) % u128::from(alpha)
+    });
+    let mut a = (p_mod_alpha as u64 + alpha - 1) % alpha;
+    let mut b = alpha;
+    while a != 0 {
+        (a, b) = (b % a, a);
+    }
+    b == 1
+}
+
+/// Checks that every square submatrix of `mds` is non-singular, which is what makes the
+/// matrix maximum distance separable.
+pub fn is_mds<F: PrimeField>(mds: &[Vec<F>]) -> bool {
+    let width = mds.len();
+    if width == 0 || width > 16 || mds.iter().any(|row| row.len() != width) {
+        return false;
+    }
+
+    // every pair of equally sized row and column subsets selects one submatrix
+    let subsets = 1u32..1 << width;
+    subsets.clone().all(|rows| {
+        subsets
+            .clone()
+            .filter(|cols| cols.count_ones() == rows.count_ones())
+            .all(|cols| {
+                let submatrix = (0..width)
+                    .filter(|i| rows & (1 << i) != 0)
+                    .map(|i| {
+                        (0..width)
+                            .filter(|j| cols & (1 << j) != 0)
+                            .map(|j| mds[i][j])
+                            .collect()
+                    })
+                    .collect();
+                !determinant(submatrix).is_zero()
+            })
+    })
+}
+
+// Gaussian elimination over F
+fn determinant<F: PrimeField>(mut m: Vec<Vec<F>>) -> F {
+    let mut det = F::one();
+    for col in 0..m.len() {
+        let Some(pivot) = (col..m.len()).find(|&row| !m[row][col].is_zero()) else {
+            return F::zero();
+        };
+        if pivot != col {
+            m.swap(pivot, col);
+            det = -det;
+        }
+        det *= m[col][col];
+
+        let inv = m[col][col].inverse().expect("the pivot is non-zero");
+        let pivot_row = m[col].clone();
+        for row in m.iter_mut().skip(col + 1) {
+            let factor = row[col] * inv;
+            row.iter_mut()
+                .zip(&pivot_row)
+                .skip(col)
+                .for_each(|(x, p)| *x -= factor * p);
+        }
+    }
+    det
+}
+
+/// Decomposes `params` into its full rounds, partial rounds, S-box exponent, MDS matrix and
+/// round constants, in the order taken by `PoseidonParameters::new`.
+pub fn poseidon_params_parts<F: PrimeField>(
+    params: &PoseidonParameters<F>,
+) -> (u32, u32, u64, Vec<Vec<F>>, Vec<Vec<F>>) {
+    // the native parameters don't expose their fields, the in-circuit sponge copies them into
+    // public ones on construction
+    let sponge = PoseidonSpongeVar::new(ConstraintSystem::new_ref(), params);
+    (
+        sponge.full_rounds,
+        sponge.partial_rounds,
+        sponge.alpha,
+        sponge.mds,
+        sponge.ark,
     )
 }
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize)]
+struct PoseidonParametersRepr {
+    rate: usize,
+    capacity: usize,
+    alpha: u64,
+    full_rounds: u32,
+    partial_rounds: u32,
+    mds: Vec<Vec<String>>,
+    ark: Vec<Vec<String>>,
+}
+
+/// Exports `params` as a JSON document for implementations outside of Rust, with the MDS matrix
+/// and round constants as decimal strings of their canonical representation.
+#[cfg(feature = "serde")]
+pub fn poseidon_params_to_json<F: PrimeField>(params: &PoseidonParameters<F>) -> String {
+    let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(params);
+    let to_decimal = |rows: Vec<Vec<F>>| {
+        rows.iter()
+            .map(|row| row.iter().map(field_to_decimal).collect())
+            .collect()
+    };
+
+    let repr = PoseidonParametersRepr {
+        rate: POSEIDON_RATE,
+        capacity: 1,
+        alpha,
+        full_rounds,
+        partial_rounds,
+        mds: to_decimal(mds),
+        ark: to_decimal(ark),
+    };
+    serde_json::to_string_pretty(&repr).expect("serializing into a string can't fail")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "bn254")]
+    use crate::{BabyJubJub, BabyJubJubVar, Bn254};
+    #[cfg(any(feature = "bn254", feature = "pasta"))]
+    use crate::{EncryptCircuit, Parameters};
+    #[cfg(feature = "pasta")]
+    use crate::{Pallas, PallasVar, Vesta, VestaVar};
+    #[cfg(any(feature = "bn254", feature = "pasta"))]
+    use ark_std::test_rng;
+
+    #[test]
+    fn higher_security_levels_take_more_partial_rounds() {
+        use crate::{JubJub, Parameters};
+
+        let standard = get_poseidon_params_for::<JubJub>(POSEIDON_RATE, 128).unwrap();
+        let high = get_poseidon_params_for::<JubJub>(POSEIDON_RATE, 256).unwrap();
+        let (full_128, partial_128, alpha_128, _, _) = poseidon_params_parts(&standard);
+        let (full_256, partial_256, alpha_256, _, _) = poseidon_params_parts(&high);
+        assert!(partial_256 > partial_128);
+        assert_eq!(alpha_256, alpha_128);
+        validate_poseidon_config::<ark_ed_on_bls12_381::Fq>(
+            POSEIDON_RATE,
+            full_256 as usize,
+            partial_256 as usize,
+            alpha_256,
+        )
+        .unwrap();
+
+        let default_sponge = Parameters::<JubJub>::default_multi(3).sponge;
+        assert_eq!(
+            poseidon_params_parts(&default_sponge),
+            poseidon_params_parts(&standard)
+        );
+        assert_eq!((full_128, partial_128), (8, 31));
+
+        for (rate, security_bits) in [(POSEIDON_RATE, 192), (POSEIDON_RATE, 0), (3, 128)] {
+            assert!(matches!(
+                get_poseidon_params_for::<JubJub>(rate, security_bits),
+                Err(EncryptionError::InvalidParameters(_))
+            ));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn exported_parameters_have_the_dimensions_of_the_sponge() {
+        use crate::JubJub;
+        use ark_ed_on_bls12_381::Fq;
+
+        let params = get_poseidon_params::<JubJub>(2);
+        let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(&params);
+        assert_eq!((full_rounds, partial_rounds, alpha), (8, 31, 17));
+        let json: serde_json::Value =
+            serde_json::from_str(&poseidon_params_to_json(&params)).unwrap();
+
+        assert_eq!(json["rate"], 2);
+        assert_eq!(json["capacity"], 1);
+        assert_eq!(json["alpha"], alpha);
+        assert_eq!(json["full_rounds"], full_rounds);
+        assert_eq!(json["partial_rounds"], partial_rounds);
+
+        let rows = |key: &str| json[key].as_array().unwrap().clone();
+        assert_eq!(rows("mds").len(), 3);
+        assert_eq!(rows("ark").len(), 8 + 31);
+        for row in rows("mds").iter().chain(&rows("ark")) {
+            assert_eq!(row.as_array().unwrap().len(), 3);
+        }
+        // the decimal strings parse back into the constants
+        assert_eq!(
+            Fq::from_str(rows("mds")[1][2].as_str().unwrap()).unwrap(),
+            mds[1][2]
+        );
+        assert_eq!(
+            Fq::from_str(rows("ark")[38][0].as_str().unwrap()).unwrap(),
+            ark[38][0]
+        );
+    }
+
+    #[cfg(feature = "bn254")]
+    #[test]
+    fn bn254_params_prove_and_verify_over_baby_jubjub() {
+        type Circuit = EncryptCircuit<BabyJubJub, BabyJubJubVar>;
+        type Fq = ark_ed_on_bn254::Fq;
+
+        let poseidon = get_poseidon_params::<BabyJubJub>(2);
+        let (full_rounds, partial_rounds, alpha, mds, _) = poseidon_params_parts(&poseidon);
+        assert_eq!((full_rounds, partial_rounds, alpha), (8, 57, 5));
+        assert!(is_mds(&mds));
+
+        let mut rng = test_rng();
+        let params = Parameters::<BabyJubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(42u64), Fq::from(7u64)];
+
+        let (proving_key, vk) = Circuit::setup::<Bn254, _>(&params, &mut rng).unwrap();
+        let circuit = Circuit::new(pk, msg.clone(), params.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+        assert!(Circuit::verify(&vk, &cipher, &proof, &params).unwrap());
+        assert_eq!(Circuit::decrypt(cipher.clone(), &sk, &params).unwrap(), msg);
+
+        let mut tampered = cipher;
+        tampered.1[0] += Fq::from(1u64);
+        assert!(!Circuit::verify(&vk, &tampered, &proof, &params).unwrap());
+    }
+
+    // the pasta curves have no pairing-friendly partner and thus no Groth16, so the circuit is
+    // only checked for satisfiability
+    #[cfg(feature = "pasta")]
+    use ark_r1cs_std::groups::CurveVar;
+    #[cfg(feature = "pasta")]
+    use ark_sponge::{constraints::AbsorbGadget, Absorb};
+
+    #[cfg(feature = "pasta")]
+    fn pasta_round_trip_satisfies_the_circuit<C, CV>()
+    where
+        C: ProjectiveCurve,
+        C::BaseField: PrimeField + Absorb,
+        <C::BaseField as FromStr>::Err: Debug,
+        C::Affine: Absorb,
+        CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    {
+        use ark_ff::One;
+        use ark_relations::r1cs::ConstraintSynthesizer;
+
+        let poseidon = get_poseidon_params::<C>(2);
+        let (full_rounds, partial_rounds, alpha, mds, _) = poseidon_params_parts(&poseidon);
+        assert_eq!((full_rounds, partial_rounds, alpha), (8, 56, 5));
+        assert!(is_mds(&mds));
+
+        let mut rng = test_rng();
+        let params = Parameters::<C>::default_multi(2);
+        let (sk, pk) = EncryptCircuit::<C, CV>::keygen(&mut rng).unwrap();
+        let msg = vec![C::BaseField::from(42u64), C::BaseField::from(7u64)];
+
+        let circuit =
+            EncryptCircuit::<C, CV>::new(pk, msg.clone(), params.clone(), &mut rng).unwrap();
+        let cipher = circuit.resulted_ciphertext.clone();
+        assert_eq!(
+            EncryptCircuit::<C, CV>::decrypt(cipher, &sk, &params).unwrap(),
+            msg
+        );
+        let cs = ConstraintSystem::<C::BaseField>::new_ref();
+        circuit.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut tampered = circuit;
+        tampered.resulted_ciphertext.1[0] += C::BaseField::one();
+        let cs = ConstraintSystem::<C::BaseField>::new_ref();
+        tampered.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+
+    #[cfg(feature = "pasta")]
+    #[test]
+    fn pasta_params_encrypt_over_pallas() {
+        pasta_round_trip_satisfies_the_circuit::<Pallas, PallasVar>();
+    }
+
+    #[cfg(feature = "pasta")]
+    #[test]
+    fn pasta_params_encrypt_over_vesta() {
+        pasta_round_trip_satisfies_the_circuit::<Vesta, VestaVar>();
+    }
+}
@@ -0,0 +1,175 @@
+//! Proxy re-encryption of ciphertexts from one recipient to another.
+//!
+//! A ciphertext to Alice is unmasked with the shared point `s = a * c1`. To delegate, Alice
+//! samples a blinding scalar `t` and hands the proxy the re-encryption key `(a / t, E_B(t))`,
+//! where `E_B(t)` is a regular ciphertext of `t` to Bob. The proxy replaces `c1` by
+//! `c1' = (a / t) * c1` and attaches `E_B(t)`, after which Bob decrypts `t` and recovers
+//! `s = t * c1'`, leaving `c2` and thus the KDF untouched.
+//!
+//! The factor `a / t` is uniformly distributed as long as `t` is hidden, so the proxy learns
+//! neither `a` nor `s` under the DDH assumption and the security of the KDF that protects
+//! `E_B(t)`. The scheme is unidirectional but not collusion resistant: the proxy and Bob
+//! together recover `a = (a / t) * t`. Re-encrypted ciphertexts can't be re-encrypted again,
+//! and parameters with [`crate::Parameters::bind_key`] set are rejected, as Bob can't
+//! reproduce the binding to Alice's key.
+
+use crate::circuit::{
+    Ciphertext, EncryptCircuit, Parameters, Plaintext, PublicKey, Randomness, SecretKey,
+};
+use crate::sponge::SpongeHash;
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_r1cs_std::groups::CurveVar;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::Absorb;
+use ark_std::rand::Rng;
+use ark_std::{vec, UniformRand};
+
+/// Key that lets a proxy turn ciphertexts to the delegator into ciphertexts to the delegatee.
+#[derive(Clone, Debug)]
+pub struct ReEncryptionKey<C: ProjectiveCurve> {
+    /// `a / t`, the delegator's secret key divided by the blinding scalar.
+    pub factor: C::ScalarField,
+    /// The blinding scalar `t` encrypted to the delegatee.
+    pub blinding: Ciphertext<C>,
+}
+
+/// Output of [`EncryptCircuit::reencrypt`]: the ciphertext with `c1` re-keyed and the
+/// encrypted blinding scalar the delegatee needs to unmask it.
+#[derive(Clone, Debug)]
+pub struct ReEncryptedCiphertext<C: ProjectiveCurve> {
+    pub cipher: Ciphertext<C>,
+    pub blinding: Ciphertext<C>,
+}
+
+impl<C, CV, S> EncryptCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    /// Derives the key delegating decryption from the holder of `sk` to the owner of `pk`.
+    pub fn re_encryption_key<R: Rng>(
+        sk: &SecretKey<C>,
+        pk: &PublicKey<C>,
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<ReEncryptionKey<C>, EncryptionError> {
+        if params.bind_key {
+            return Err(EncryptionError::InvalidParameters(
+                "re-encryption doesn't support binding ciphertexts to the recipient's key".into(),
+            ));
+        }
+
+        // t travels as a base field element, so it's resampled until it fits one
+        let (t, encoded) = loop {
+            let t = C::ScalarField::rand(rng);
+            let encoded = C::BaseField::from_le_bytes_mod_order(&t.into_repr().to_bytes_le());
+            if !t.is_zero() && Self::decode_blinding(&encoded) == t {
+                break (t, encoded);
+            }
+        };
+
+        let factor = t.inverse().expect("t is non-zero") * sk.0;
+        let blinding = Self::encrypt(pk, &vec![encoded], &Randomness::rand(rng), params)?;
+        Ok(ReEncryptionKey { factor, blinding })
+    }
+
+    /// Re-keys `cipher` with `rk`, which needs neither secret key nor reveals the plaintext.
+    pub fn reencrypt(cipher: &Ciphertext<C>, rk: &ReEncryptionKey<C>) -> ReEncryptedCiphertext<C> {
+        let mut c1 = cipher.0;
+        c1.mul_assign(rk.factor);
+
+        ReEncryptedCiphertext {
+            cipher: (c1, cipher.1.clone()),
+            blinding: rk.blinding.clone(),
+        }
+    }
+
+    /// Decrypts the output of [`EncryptCircuit::reencrypt`] with the delegatee's secret key.
+    pub fn decrypt_reencrypted(
+        reencrypted: ReEncryptedCiphertext<C>,
+        sk: &SecretKey<C>,
+        params: &Parameters<C, S>,
+    ) -> Result<Plaintext<C>, EncryptionError> {
+        let blinding = Self::decrypt(reencrypted.blinding, sk, params)?;
+        let t = blinding.first().ok_or(EncryptionError::LengthMismatch {
+            expected: 1,
+            got: 0,
+        })?;
+
+        let mut s = reencrypted.cipher.0;
+        s.mul_assign(Self::decode_blinding(t));
+        Self::decrypt_with_shared_point(reencrypted.cipher, &s, params)
+    }
+
+    fn decode_blinding(encoded: &C::BaseField) -> C::ScalarField {
+        C::ScalarField::from_le_bytes_mod_order(&encoded.into_repr().to_bytes_le())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::JubJub;
+    use ark_std::test_rng;
+
+    #[test]
+    fn the_delegatee_decrypts_re_encrypted_ciphertexts() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (alice_sk, alice_pk) = Circuit::keygen(&mut rng).unwrap();
+        let (bob_sk, bob_pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+        let cipher =
+            Circuit::encrypt(&alice_pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+
+        let rk = Circuit::re_encryption_key(&alice_sk, &bob_pk, &params, &mut rng).unwrap();
+        let reencrypted = Circuit::reencrypt(&cipher, &rk);
+        assert_eq!(reencrypted.cipher.1, cipher.1);
+        assert_ne!(reencrypted.cipher.0, cipher.0);
+        assert_eq!(
+            Circuit::decrypt_reencrypted(reencrypted.clone(), &bob_sk, &params).unwrap(),
+            msg
+        );
+
+        // what the proxy holds doesn't unmask either ciphertext
+        let factor = SecretKey(rk.factor);
+        assert_ne!(
+            Circuit::decrypt(cipher.clone(), &factor, &params).unwrap(),
+            msg
+        );
+        assert_ne!(
+            Circuit::decrypt(reencrypted.cipher.clone(), &factor, &params).unwrap(),
+            msg
+        );
+        assert_ne!(
+            Circuit::decrypt(reencrypted.cipher.clone(), &alice_sk, &params).unwrap(),
+            msg
+        );
+        assert_ne!(
+            Circuit::decrypt_reencrypted(reencrypted, &alice_sk, &params).unwrap(),
+            msg
+        );
+    }
+
+    #[test]
+    fn key_bound_parameters_are_rejected() {
+        let mut rng = test_rng();
+        let params = Parameters {
+            bind_key: true,
+            ..Parameters::<JubJub>::default_multi(1)
+        };
+        let (alice_sk, _) = Circuit::keygen(&mut rng).unwrap();
+        let (_, bob_pk) = Circuit::keygen(&mut rng).unwrap();
+        assert!(matches!(
+            Circuit::re_encryption_key(&alice_sk, &bob_pk, &params, &mut rng),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+    }
+}
@@ -0,0 +1,290 @@
+use crate::circuit::{Ciphertext, EncryptCircuit, Parameters, Plaintext, PublicKey, Randomness};
+use crate::sponge::{PoseidonHash, SpongeHash};
+use crate::EncryptionError;
+use ark_ec::{PairingEngine, ProjectiveCurve};
+use ark_ff::{One, PrimeField, ToConstraintField, Zero};
+use ark_groth16::{Groth16, Proof, ProvingKey, VerifyingKey};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::groups::CurveVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::ns;
+use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError};
+use ark_snark::SNARK;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::Absorb;
+use ark_std::marker::PhantomData;
+use ark_std::rand::{CryptoRng, Rng};
+use ark_std::vec::Vec;
+use ark_std::{vec, UniformRand};
+
+/// Proves that one ciphertext is a re-encryption of another to the same public key, i.e. that
+/// both encrypt the same message under their own randomness, without revealing the message or
+/// either randomness. This is the per-ciphertext step of a verifiable shuffle.
+///
+/// Like [`crate::MultiEncryptCircuit`], the circuit expects the message to fill all
+/// `params.n` slots.
+#[derive(Clone)]
+pub struct ReEncryptionCircuit<C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    old_r: Randomness<C>,
+    new_r: Randomness<C>,
+    msg: Plaintext<C>,
+    pk: PublicKey<C>,
+    pub old_ciphertext: Ciphertext<C>,
+    pub resulted_ciphertext: Ciphertext<C>,
+    params: Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, S> ReEncryptionCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    /// Re-encrypts the encryption of `msg` to `pk` under `old_r` with fresh randomness.
+    pub fn new<R: Rng>(
+        pk: PublicKey<C>,
+        msg: Plaintext<C>,
+        old_r: Randomness<C>,
+        params: Parameters<C, S>,
+        rnd: &mut R,
+    ) -> Result<Self, EncryptionError> {
+        Self::with_randomness(pk, msg, old_r, Randomness::rand(rnd), params)
+    }
+
+    fn with_randomness(
+        pk: PublicKey<C>,
+        msg: Plaintext<C>,
+        old_r: Randomness<C>,
+        new_r: Randomness<C>,
+        params: Parameters<C, S>,
+    ) -> Result<Self, EncryptionError> {
+        if msg.len() != params.n {
+            return Err(EncryptionError::LengthMismatch {
+                expected: params.n,
+                got: msg.len(),
+            });
+        }
+
+        let old_ciphertext = EncryptCircuit::<C, CV, S>::encrypt(&pk, &msg, &old_r, &params)?;
+        let resulted_ciphertext = EncryptCircuit::<C, CV, S>::encrypt(&pk, &msg, &new_r, &params)?;
+
+        Ok(Self {
+            old_r,
+            new_r,
+            msg,
+            pk,
+            old_ciphertext,
+            resulted_ciphertext,
+            params,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Public inputs in allocation order: the public key, then `c1` and `c2` of the old and of
+    /// the new ciphertext.
+    pub fn get_public_inputs<E: PairingEngine>(
+        pk: &PublicKey<C>,
+        old: &Ciphertext<C>,
+        new: &Ciphertext<C>,
+    ) -> Vec<E::Fr>
+    where
+        C::BaseField: ToConstraintField<E::Fr>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let mut inputs = pk.to_field_elements().unwrap();
+        for (c1, c2) in [old, new] {
+            inputs.extend(c1.to_field_elements().unwrap());
+            inputs.extend(c2.iter().flat_map(|f| f.to_field_elements().unwrap()));
+        }
+        inputs
+    }
+
+    /// Runs the Groth16 setup for circuits of the shape given by `params`.
+    pub fn setup<E, R>(
+        params: &Parameters<C, S>,
+        rng: &mut R,
+    ) -> Result<(ProvingKey<E>, VerifyingKey<E>), EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        let blank = Self::with_randomness(
+            params.generator,
+            vec![C::BaseField::zero(); params.n],
+            Randomness(C::ScalarField::one()),
+            Randomness(C::ScalarField::one()),
+            params.clone(),
+        )?;
+        Ok(Groth16::<E>::circuit_specific_setup(blank, rng)?)
+    }
+
+    pub fn prove<E, R>(&self, pk: &ProvingKey<E>, rng: &mut R) -> Result<Proof<E>, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        R: Rng + CryptoRng,
+    {
+        Ok(Groth16::<E>::prove(pk, self.clone(), rng)?)
+    }
+
+    pub fn verify<E>(
+        vk: &VerifyingKey<E>,
+        pk: &PublicKey<C>,
+        old: &Ciphertext<C>,
+        new: &Ciphertext<C>,
+        proof: &Proof<E>,
+    ) -> Result<bool, EncryptionError>
+    where
+        E: PairingEngine<Fr = C::BaseField>,
+        C: ToConstraintField<E::Fr>,
+    {
+        let public_inputs = Self::get_public_inputs::<E>(pk, old, new);
+        Ok(Groth16::<E>::verify(vk, &public_inputs, proof)?)
+    }
+
+    // enforce c1 = randomness*generator and c2_i = m_i + dh_i under s = randomness*pk
+    fn enforce_encryption(
+        &self,
+        cs: ConstraintSystemRef<C::BaseField>,
+        pk: &CV,
+        cipher: &(CV, Vec<FpVar<C::BaseField>>),
+        message: &[FpVar<C::BaseField>],
+        r: &Randomness<C>,
+    ) -> Result<(), SynthesisError> {
+        let (c1, c2) = cipher;
+
+        let randomness = EncryptCircuit::<C, CV, S>::scalar_bits_var(ns!(cs, "randomness"), &r.0)?;
+
+        EncryptCircuit::<C, CV, S>::generator_mul_var(&randomness, &self.params.generator)?
+            .enforce_equal(c1)?;
+
+        let s = pk.scalar_mul_le(randomness.iter())?;
+        let kdf_inputs = EncryptCircuit::<C, CV, S>::constant_kdf_inputs(&self.params);
+        let sponge =
+            EncryptCircuit::<C, CV, S>::kdf_sponge_var(cs, &s, pk, c1, &kdf_inputs, &self.params)?;
+
+        let masks = EncryptCircuit::<C, CV, S>::slot_masks_var(&sponge, c2.len(), &self.params)?;
+        message
+            .iter()
+            .zip(c2.iter())
+            .zip(masks)
+            .try_for_each(|((m, c2i), dh)| (m + dh).enforce_equal(c2i))
+    }
+}
+
+impl<C, CV, S> ConstraintSynthesizer<C::BaseField> for ReEncryptionCircuit<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AllocVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<C::BaseField>,
+    ) -> Result<(), SynthesisError> {
+        if self.msg.len() != self.params.n
+            || self.old_ciphertext.1.len() != self.params.n
+            || self.resulted_ciphertext.1.len() != self.params.n
+        {
+            return Err(SynthesisError::Unsatisfiable);
+        }
+
+        let pk = CV::new_input(ns!(cs, "pub_key"), || Ok(self.pk))?;
+        let [old, new] = [&self.old_ciphertext, &self.resulted_ciphertext].map(|(c1, c2)| {
+            let c1 = CV::new_input(ns!(cs, "ciphertext"), || Ok(*c1))?;
+            let c2 =
+                Vec::<FpVar<C::BaseField>>::new_input(ns!(cs, "ciphertext"), || Ok(c2.as_slice()))?;
+            Ok::<_, SynthesisError>((c1, c2))
+        });
+        let (old, new) = (old?, new?);
+        // one message witness for both ciphertexts is what ties them to the same plaintext
+        let message =
+            Vec::<FpVar<C::BaseField>>::new_witness(ns!(cs, "plaintext"), || Ok(self.msg.clone()))?;
+
+        self.enforce_encryption(cs.clone(), &pk, &old, &message, &self.old_r)?;
+        self.enforce_encryption(cs, &pk, &new, &message, &self.new_r)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Bls12_381, JubJub, JubJubVar};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_relations::r1cs::ConstraintSystem;
+    use ark_std::test_rng;
+
+    type Circuit = ReEncryptionCircuit<JubJub, JubJubVar>;
+    type Encrypt = EncryptCircuit<JubJub, JubJubVar>;
+
+    #[test]
+    fn re_encryptions_of_the_same_message_prove_and_verify() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Encrypt::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(4u64), Fq::from(2u64)];
+
+        let circuit = Circuit::new(
+            pk,
+            msg.clone(),
+            Randomness::rand(&mut rng),
+            params.clone(),
+            &mut rng,
+        )
+        .unwrap();
+        let (old, new) = (
+            circuit.old_ciphertext.clone(),
+            circuit.resulted_ciphertext.clone(),
+        );
+        assert_ne!(old, new);
+        assert_eq!(Encrypt::decrypt(new.clone(), &sk, &params).unwrap(), msg);
+
+        let (proving_key, vk) = Circuit::setup::<Bls12_381, _>(&params, &mut rng).unwrap();
+        let proof = circuit.prove(&proving_key, &mut rng).unwrap();
+        assert!(Circuit::verify(&vk, &pk, &old, &new, &proof).unwrap());
+
+        // a fresh encryption of another message doesn't pass as the re-encryption
+        let other = vec![Fq::from(4u64), Fq::from(3u64)];
+        let forged = Encrypt::encrypt(&pk, &other, &Randomness::rand(&mut rng), &params).unwrap();
+        assert!(!Circuit::verify(&vk, &pk, &old, &forged, &proof).unwrap());
+    }
+
+    #[test]
+    fn re_encryptions_of_another_message_are_unsatisfiable() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (_, pk) = Encrypt::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(4u64), Fq::from(2u64)];
+
+        let honest = Circuit::new(
+            pk,
+            msg,
+            Randomness::rand(&mut rng),
+            params.clone(),
+            &mut rng,
+        )
+        .unwrap();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        honest.clone().generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut forged = honest;
+        let other = vec![Fq::from(4u64), Fq::from(3u64)];
+        forged.resulted_ciphertext = Encrypt::encrypt(&pk, &other, &forged.new_r, &params).unwrap();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        forged.generate_constraints(cs.clone()).unwrap();
+        assert!(!cs.is_satisfied().unwrap());
+    }
+}
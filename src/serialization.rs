@@ -0,0 +1,165 @@
+use crate::circuit::Ciphertext;
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{BigInteger, FromBytes, PrimeField};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::{format, string::String, vec::Vec};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// Thin wrapper making `Ciphertext<C>` (de)serializable with serde.
+///
+/// `c1` is encoded as its compressed affine form and every `c2` element as the canonical
+/// little-endian bytes of its big-integer representation, both hex-encoded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SerializableCiphertext<C: ProjectiveCurve>(pub Ciphertext<C>);
+
+impl<C: ProjectiveCurve> From<Ciphertext<C>> for SerializableCiphertext<C> {
+    fn from(cipher: Ciphertext<C>) -> Self {
+        Self(cipher)
+    }
+}
+
+impl<C: ProjectiveCurve> From<SerializableCiphertext<C>> for Ciphertext<C> {
+    fn from(wrapper: SerializableCiphertext<C>) -> Self {
+        wrapper.0
+    }
+}
+
+pub(crate) fn point_to_bytes<C: ProjectiveCurve>(p: &C) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    p.into_affine()
+        .serialize(&mut bytes)
+        .expect("serializing into a vector can't fail");
+    bytes
+}
+
+pub(crate) fn point_from_bytes<C: ProjectiveCurve>(bytes: &[u8]) -> Option<C> {
+    let mut reader = bytes;
+    let p = C::Affine::deserialize(&mut reader).ok()?;
+    reader.is_empty().then(|| p.into_projective())
+}
+
+pub(crate) fn field_to_bytes<F: PrimeField>(f: &F) -> Vec<u8> {
+    f.into_repr().to_bytes_le()
+}
+
+pub(crate) fn field_from_bytes<F: PrimeField>(bytes: &[u8]) -> Option<F> {
+    let mut reader = bytes;
+    let repr = F::BigInt::read(&mut reader).ok()?;
+    if !reader.is_empty() {
+        return None;
+    }
+    // rejects non-canonical encodings, i.e. ones exceeding the modulus
+    F::from_repr(repr)
+}
+
+// decimal digits of the canonical representation, by repeated division of the little-endian
+// limbs by the largest power of ten fitting a limb
+pub(crate) fn field_to_decimal<F: PrimeField>(f: &F) -> String {
+    const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+    let mut limbs = f.into_repr().as_ref().to_vec();
+    let mut chunks = Vec::new();
+    while limbs.iter().any(|&l| l != 0) {
+        let mut rem = 0u128;
+        for limb in limbs.iter_mut().rev() {
+            let cur = (rem << 64) | u128::from(*limb);
+            *limb = (cur / u128::from(CHUNK)) as u64;
+            rem = cur % u128::from(CHUNK);
+        }
+        chunks.push(rem as u64);
+    }
+
+    match chunks.split_last() {
+        None => String::from("0"),
+        Some((most_significant, rest)) => {
+            let mut s = format!("{most_significant}");
+            rest.iter()
+                .rev()
+                .for_each(|c| s.push_str(&format!("{c:019}")));
+            s
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CiphertextRepr {
+    c1: String,
+    c2: Vec<String>,
+}
+
+impl<C: ProjectiveCurve> Serialize for SerializableCiphertext<C>
+where
+    C::BaseField: PrimeField,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (c1, c2) = &self.0;
+        CiphertextRepr {
+            c1: hex::encode(point_to_bytes(c1)),
+            c2: c2.iter().map(|c| hex::encode(field_to_bytes(c))).collect(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, C: ProjectiveCurve> Deserialize<'de> for SerializableCiphertext<C>
+where
+    C::BaseField: PrimeField,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = CiphertextRepr::deserialize(deserializer)?;
+
+        let c1 = hex::decode(&repr.c1)
+            .ok()
+            .and_then(|bytes| point_from_bytes::<C>(&bytes))
+            .ok_or_else(|| de::Error::custom("invalid ciphertext point c1"))?;
+        let c2 = repr
+            .c2
+            .iter()
+            .map(|c| {
+                hex::decode(c)
+                    .ok()
+                    .and_then(|bytes| field_from_bytes::<C::BaseField>(&bytes))
+                    .ok_or_else(|| de::Error::custom("invalid ciphertext element in c2"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self((c1, c2)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{Bls12_381, JubJub, Parameters, Randomness};
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn ciphertexts_round_trip_through_json() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(4);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        // a short message padded with zeros up to n, which have to survive the round trip
+        let mut msg = message(2);
+        msg.resize(params.n, Default::default());
+        let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+
+        let json = serde_json::to_string(&SerializableCiphertext(cipher.clone())).unwrap();
+        let back: Ciphertext<JubJub> =
+            serde_json::from_str::<SerializableCiphertext<JubJub>>(&json)
+                .unwrap()
+                .into();
+        assert_eq!(back, cipher);
+        assert_eq!(back.1.len(), params.n);
+        assert_eq!(
+            Circuit::get_public_inputs::<Bls12_381>(&back, &params),
+            Circuit::get_public_inputs::<Bls12_381>(&cipher, &params)
+        );
+        assert_eq!(Circuit::decrypt(back, &sk, &params).unwrap(), msg);
+
+        assert!(
+            serde_json::from_str::<SerializableCiphertext<JubJub>>(r#"{"c1":"00","c2":[]}"#)
+                .is_err()
+        );
+    }
+}
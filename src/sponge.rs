@@ -0,0 +1,453 @@
+//! Sponges the encryption KDF can be instantiated with.
+
+use crate::poseidon::{
+    find_poseidon_ark_and_mds, poseidon_params_parts, validate_poseidon_config, POSEIDON_RATE,
+};
+use crate::EncryptionError;
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_r1cs_std::fields::fp::FpVar;
+use ark_r1cs_std::prelude::*;
+use ark_relations::r1cs::{ConstraintSystemRef, SynthesisError};
+use ark_sponge::constraints::{AbsorbGadget, CryptographicSpongeVar};
+use ark_sponge::poseidon::constraints::PoseidonSpongeVar;
+use ark_sponge::poseidon::{PoseidonParameters, PoseidonSponge};
+use ark_sponge::{Absorb, CryptographicSponge, FieldBasedCryptographicSponge, FieldElementSize};
+use ark_std::fmt::Debug;
+use ark_std::{vec, vec::Vec};
+
+/// Pairs a native sponge over `F` with its in-circuit counterpart, both constructed from the
+/// same parameters.
+///
+/// The masks derived natively have to match the ones derived in the circuit, so the two sponges
+/// must absorb and squeeze field elements identically.
+pub trait SpongeHash<F: PrimeField>: Clone + Debug + Send + Sync {
+    type Parameters: Clone + Debug + Send + Sync;
+    type Sponge: CryptographicSponge<Parameters = Self::Parameters>;
+    type SpongeVar: CryptographicSpongeVar<F, Self::Sponge, Parameters = Self::Parameters>;
+}
+
+/// The Poseidon sponge of `ark-sponge`, used by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoseidonHash;
+
+impl<F: PrimeField> SpongeHash<F> for PoseidonHash {
+    type Parameters = PoseidonParameters<F>;
+    type Sponge = PoseidonSponge<F>;
+    type SpongeVar = PoseidonSpongeVar<F>;
+}
+
+/// Poseidon parameters for a state of `rate + 1` elements. Unlike [`PoseidonParameters`], whose
+/// constructor only accepts round constants for a state of 3 elements, the rate is free.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WidePoseidonParameters<F: PrimeField> {
+    pub rate: usize,
+    pub full_rounds: u32,
+    pub partial_rounds: u32,
+    pub alpha: u64,
+    pub mds: Vec<Vec<F>>,
+    pub ark: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> WidePoseidonParameters<F> {
+    /// Generates the round constants and MDS matrix for the given configuration with the Grain
+    /// LFSR, after checking it with [`validate_poseidon_config`].
+    pub fn new(
+        rate: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+        alpha: u64,
+    ) -> Result<Self, EncryptionError> {
+        validate_poseidon_config::<F>(rate, full_rounds, partial_rounds, alpha)?;
+
+        let (ark, mds) =
+            find_poseidon_ark_and_mds::<F>(rate, full_rounds as u64, partial_rounds as u64, 0);
+        Ok(Self {
+            rate,
+            full_rounds: full_rounds as u32,
+            partial_rounds: partial_rounds as u32,
+            alpha,
+            mds,
+            ark,
+        })
+    }
+}
+
+// the configuration of an `ark-sponge` sponge, whose rate is always 2
+impl<F: PrimeField> From<&PoseidonParameters<F>> for WidePoseidonParameters<F> {
+    fn from(params: &PoseidonParameters<F>) -> Self {
+        let (full_rounds, partial_rounds, alpha, mds, ark) = poseidon_params_parts(params);
+        Self {
+            rate: POSEIDON_RATE,
+            full_rounds,
+            partial_rounds,
+            alpha,
+            mds,
+            ark,
+        }
+    }
+}
+
+/// The Poseidon duplex sponge of `ark-sponge` with the rate taken from its parameters instead
+/// of fixed to 2, keeping a capacity of one element. At rate 2 it derives the same output as
+/// [`PoseidonHash`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WidePoseidonHash;
+
+impl<F: PrimeField> SpongeHash<F> for WidePoseidonHash {
+    type Parameters = WidePoseidonParameters<F>;
+    type Sponge = WidePoseidonSponge<F>;
+    type SpongeVar = WidePoseidonSpongeVar<F>;
+}
+
+// the next position of the rate to absorb into or squeeze from
+#[derive(Clone, Copy, Debug)]
+enum SpongeMode {
+    Absorbing(usize),
+    Squeezing(usize),
+}
+
+/// Native sponge of [`WidePoseidonHash`].
+#[derive(Clone, Debug)]
+pub struct WidePoseidonSponge<F: PrimeField> {
+    params: WidePoseidonParameters<F>,
+    state: Vec<F>,
+    mode: SpongeMode,
+}
+
+impl<F: PrimeField> WidePoseidonSponge<F> {
+    fn permute(&mut self) {
+        let params = &self.params;
+        let half_full = params.full_rounds / 2;
+        let state = &mut self.state;
+        for (round, ark) in params.ark.iter().enumerate() {
+            state.iter_mut().zip(ark).for_each(|(s, c)| *s += c);
+            let round = round as u32;
+            if round < half_full || round >= half_full + params.partial_rounds {
+                state.iter_mut().for_each(|s| *s = s.pow([params.alpha]));
+            } else {
+                let last = state.len() - 1;
+                state[last] = state[last].pow([params.alpha]);
+            }
+            *state = params
+                .mds
+                .iter()
+                .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum())
+                .collect();
+        }
+    }
+
+    fn absorb_native(&mut self, elems: &[F]) {
+        if elems.is_empty() {
+            return;
+        }
+        let mut next = match self.mode {
+            SpongeMode::Absorbing(next) => next,
+            SpongeMode::Squeezing(_) => self.params.rate,
+        };
+        for elem in elems {
+            if next == self.params.rate {
+                self.permute();
+                next = 0;
+            }
+            self.state[next] += elem;
+            next += 1;
+        }
+        self.mode = SpongeMode::Absorbing(next);
+    }
+
+    // switching to squeezing permutes right away, even for no output, like `ark-sponge` does
+    fn squeeze_native(&mut self, num_elements: usize) -> Vec<F> {
+        let mut next = match self.mode {
+            SpongeMode::Squeezing(next) if next < self.params.rate => next,
+            _ => {
+                self.permute();
+                0
+            }
+        };
+        let out = (0..num_elements)
+            .map(|_| {
+                if next == self.params.rate {
+                    self.permute();
+                    next = 0;
+                }
+                next += 1;
+                self.state[next - 1]
+            })
+            .collect();
+        self.mode = SpongeMode::Squeezing(next);
+        out
+    }
+}
+
+impl<F: PrimeField> CryptographicSponge for WidePoseidonSponge<F> {
+    type Parameters = WidePoseidonParameters<F>;
+
+    fn new(params: &Self::Parameters) -> Self {
+        Self {
+            params: params.clone(),
+            state: vec![F::zero(); params.rate + 1],
+            mode: SpongeMode::Absorbing(0),
+        }
+    }
+
+    fn absorb(&mut self, input: &impl Absorb) {
+        self.absorb_native(&input.to_sponge_field_elements_as_vec::<F>());
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Vec<u8> {
+        let usable_bytes = (F::Params::CAPACITY / 8) as usize;
+        let mut bytes = self
+            .squeeze_native(num_bytes.div_ceil(usable_bytes))
+            .iter()
+            .flat_map(|elem| elem.into_repr().to_bytes_le()[..usable_bytes].to_vec())
+            .collect::<Vec<_>>();
+        bytes.truncate(num_bytes);
+        bytes
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        let usable_bits = F::Params::CAPACITY as usize;
+        let mut bits = self
+            .squeeze_native(num_bits.div_ceil(usable_bits))
+            .iter()
+            .flat_map(|elem| elem.into_repr().to_bits_le()[..usable_bits].to_vec())
+            .collect::<Vec<_>>();
+        bits.truncate(num_bits);
+        bits
+    }
+
+    // native elements are squeezed as they are, others fall back to the bit decomposition
+    fn squeeze_field_elements<F2: PrimeField>(&mut self, num_elements: usize) -> Vec<F2> {
+        if F::characteristic() != F2::characteristic() {
+            return self.squeeze_field_elements_with_sizes::<F2>(&vec![
+                FieldElementSize::Full;
+                num_elements
+            ]);
+        }
+        self.squeeze_native(num_elements)
+            .iter()
+            .map(|elem| F2::from_le_bytes_mod_order(&elem.into_repr().to_bytes_le()))
+            .collect()
+    }
+}
+
+impl<F: PrimeField> FieldBasedCryptographicSponge<F> for WidePoseidonSponge<F> {
+    fn squeeze_native_field_elements(&mut self, num_elements: usize) -> Vec<F> {
+        self.squeeze_native(num_elements)
+    }
+}
+
+/// In-circuit sponge of [`WidePoseidonHash`], absorbing and squeezing like
+/// [`WidePoseidonSponge`].
+#[derive(Clone)]
+pub struct WidePoseidonSpongeVar<F: PrimeField> {
+    cs: ConstraintSystemRef<F>,
+    params: WidePoseidonParameters<F>,
+    state: Vec<FpVar<F>>,
+    mode: SpongeMode,
+}
+
+impl<F: PrimeField> WidePoseidonSpongeVar<F> {
+    #[tracing::instrument(target = "r1cs", skip(self))]
+    fn permute(&mut self) -> Result<(), SynthesisError> {
+        let params = &self.params;
+        let half_full = params.full_rounds / 2;
+        let state = &mut self.state;
+        for (round, ark) in params.ark.iter().enumerate() {
+            state.iter_mut().zip(ark).for_each(|(s, c)| *s += *c);
+            let round = round as u32;
+            if round < half_full || round >= half_full + params.partial_rounds {
+                for s in state.iter_mut() {
+                    *s = s.pow_by_constant([params.alpha])?;
+                }
+            } else {
+                let last = state.len() - 1;
+                state[last] = state[last].pow_by_constant([params.alpha])?;
+            }
+            *state = params
+                .mds
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(state.iter())
+                        .fold(FpVar::zero(), |acc, (m, s)| acc + s * *m)
+                })
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn squeeze_native(&mut self, num_elements: usize) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        let mut next = match self.mode {
+            SpongeMode::Squeezing(next) if next < self.params.rate => next,
+            _ => {
+                self.permute()?;
+                0
+            }
+        };
+        let mut out = Vec::with_capacity(num_elements);
+        for _ in 0..num_elements {
+            if next == self.params.rate {
+                self.permute()?;
+                next = 0;
+            }
+            out.push(self.state[next].clone());
+            next += 1;
+        }
+        self.mode = SpongeMode::Squeezing(next);
+        Ok(out)
+    }
+}
+
+impl<F: PrimeField> CryptographicSpongeVar<F, WidePoseidonSponge<F>> for WidePoseidonSpongeVar<F> {
+    type Parameters = WidePoseidonParameters<F>;
+
+    fn new(cs: ConstraintSystemRef<F>, params: &Self::Parameters) -> Self {
+        Self {
+            cs,
+            params: params.clone(),
+            state: vec![FpVar::zero(); params.rate + 1],
+            mode: SpongeMode::Absorbing(0),
+        }
+    }
+
+    fn cs(&self) -> ConstraintSystemRef<F> {
+        self.cs.clone()
+    }
+
+    fn absorb(&mut self, input: &impl AbsorbGadget<F>) -> Result<(), SynthesisError> {
+        let elems = input.to_sponge_field_elements()?;
+        if elems.is_empty() {
+            return Ok(());
+        }
+        let mut next = match self.mode {
+            SpongeMode::Absorbing(next) => next,
+            SpongeMode::Squeezing(_) => self.params.rate,
+        };
+        for elem in &elems {
+            if next == self.params.rate {
+                self.permute()?;
+                next = 0;
+            }
+            self.state[next] += elem;
+            next += 1;
+        }
+        self.mode = SpongeMode::Absorbing(next);
+        Ok(())
+    }
+
+    fn squeeze_bytes(&mut self, num_bytes: usize) -> Result<Vec<UInt8<F>>, SynthesisError> {
+        let usable_bytes = (F::Params::CAPACITY / 8) as usize;
+        let mut bytes = Vec::new();
+        for elem in self.squeeze_native(num_bytes.div_ceil(usable_bytes))? {
+            bytes.extend_from_slice(&elem.to_bytes()?[..usable_bytes]);
+        }
+        bytes.truncate(num_bytes);
+        Ok(bytes)
+    }
+
+    fn squeeze_bits(&mut self, num_bits: usize) -> Result<Vec<Boolean<F>>, SynthesisError> {
+        let usable_bits = F::Params::CAPACITY as usize;
+        let mut bits = Vec::new();
+        for elem in self.squeeze_native(num_bits.div_ceil(usable_bits))? {
+            bits.extend_from_slice(&elem.to_bits_le()?[..usable_bits]);
+        }
+        bits.truncate(num_bits);
+        Ok(bits)
+    }
+
+    fn squeeze_field_elements(
+        &mut self,
+        num_elements: usize,
+    ) -> Result<Vec<FpVar<F>>, SynthesisError> {
+        self.squeeze_native(num_elements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::poseidon::default_alpha;
+    use crate::{EncryptCircuit, JubJub, JubJubVar, MaskDerivation, Parameters, Randomness};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem};
+    use ark_std::{test_rng, UniformRand};
+
+    type WideCircuit = EncryptCircuit<JubJub, JubJubVar, WidePoseidonHash>;
+
+    // absorbs and squeezes in uneven amounts, so that both rate boundaries and mode switches
+    // fall in the middle of calls
+    fn squeezed_natively_and_in_circuit(params: &WidePoseidonParameters<Fq>) -> (Vec<Fq>, Vec<Fq>) {
+        let mut rng = test_rng();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        let mut native = WidePoseidonSponge::new(params);
+        let mut gadget = WidePoseidonSpongeVar::new(cs.clone(), params);
+
+        let (mut out, mut out_var) = (Vec::new(), Vec::new());
+        for (absorbed, squeezed) in [(3, 1), (0, 4), (7, 0), (1, 2)] {
+            let elems = (0..absorbed)
+                .map(|_| Fq::rand(&mut rng))
+                .collect::<Vec<_>>();
+            let vars = elems
+                .iter()
+                .map(|e| FpVar::new_witness(cs.clone(), || Ok(*e)).unwrap())
+                .collect::<Vec<_>>();
+            native.absorb(&elems);
+            gadget.absorb(&vars).unwrap();
+            out.extend(native.squeeze_field_elements::<Fq>(squeezed));
+            out_var.extend(
+                gadget
+                    .squeeze_field_elements(squeezed)
+                    .unwrap()
+                    .value()
+                    .unwrap(),
+            );
+        }
+        assert!(cs.is_satisfied().unwrap());
+        (out, out_var)
+    }
+
+    #[test]
+    fn native_and_in_circuit_sponges_agree_at_every_rate() {
+        let default = Parameters::<JubJub>::default_multi(1).sponge;
+        let (native, in_circuit) = squeezed_natively_and_in_circuit(&(&default).into());
+        assert_eq!(native, in_circuit);
+
+        for rate in [1, 3, 4] {
+            let params =
+                WidePoseidonParameters::<Fq>::new(rate, 8, 57, default_alpha::<Fq>()).unwrap();
+            let (native, in_circuit) = squeezed_natively_and_in_circuit(&params);
+            assert_eq!(native, in_circuit);
+        }
+        assert!(WidePoseidonParameters::<Fq>::new(0, 8, 57, default_alpha::<Fq>()).is_err());
+    }
+
+    #[test]
+    fn wide_sponges_at_rate_two_match_the_poseidon_sponge() {
+        let default = Parameters::<JubJub>::default_multi(3);
+        let wide = Parameters::<JubJub, WidePoseidonHash> {
+            n: default.n,
+            sponge: (&default.sponge).into(),
+            domain_tag: None,
+            bind_key: false,
+            mask_derivation: MaskDerivation::Squeezed,
+            generator: default.generator,
+        };
+        let mut default = default;
+        default.mask_derivation = MaskDerivation::Squeezed;
+
+        let mut rng = test_rng();
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = message(3);
+        let r = Randomness::rand(&mut rng);
+        let cipher = WideCircuit::encrypt(&pk, &msg, &r, &wide).unwrap();
+        assert_eq!(cipher, Circuit::encrypt(&pk, &msg, &r, &default).unwrap());
+        assert_eq!(WideCircuit::decrypt(cipher, &sk, &wide).unwrap(), msg);
+
+        let circuit = WideCircuit::new(pk, msg, wide, &mut rng).unwrap();
+        let cs = ConstraintSystem::<Fq>::new_ref();
+        circuit.generate_constraints(cs.clone()).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+    }
+}
@@ -0,0 +1,188 @@
+use crate::circuit::{
+    Ciphertext, EncryptCircuit, Parameters, Plaintext, PublicKey, Randomness, SecretKey,
+};
+use crate::sponge::{PoseidonHash, SpongeHash};
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_ff::PrimeField;
+use ark_r1cs_std::groups::CurveVar;
+use ark_sponge::constraints::AbsorbGadget;
+use ark_sponge::Absorb;
+use ark_std::marker::PhantomData;
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+
+/// Encrypts messages longer than `params.n` as a sequence of ciphertexts of `n` slots each.
+///
+/// Every block is encrypted under fresh randomness and the last one only holds the remaining
+/// slots, so the stream length is carried by the ciphertexts themselves rather than by
+/// padding. Each ciphertext can be proven on its own with circuits of `params.n` slots.
+pub struct StreamEncryptor<C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pk: PublicKey<C>,
+    params: Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, S> StreamEncryptor<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pub fn new(pk: PublicKey<C>, params: Parameters<C, S>) -> Result<Self, EncryptionError> {
+        EncryptCircuit::<C, CV, S>::validate_public_key(&pk)?;
+        if params.n == 0 {
+            return Err(EncryptionError::InvalidParameters(
+                "streams need blocks of at least one slot".into(),
+            ));
+        }
+
+        Ok(Self {
+            pk,
+            params,
+            _curve_var: PhantomData,
+        })
+    }
+
+    /// Splits `msg` into blocks of `params.n` slots and encrypts each of them, an empty message
+    /// yields no ciphertexts.
+    pub fn encrypt<R: Rng>(
+        &self,
+        msg: &[C::BaseField],
+        rng: &mut R,
+    ) -> Result<Vec<Ciphertext<C>>, EncryptionError> {
+        msg.chunks(self.params.n)
+            .map(|block| {
+                EncryptCircuit::<C, CV, S>::encrypt(
+                    &self.pk,
+                    &block.to_vec(),
+                    &Randomness::rand(rng),
+                    &self.params,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Reverses [`StreamEncryptor`], concatenating the decrypted blocks of a stream.
+pub struct StreamDecryptor<C, CV, S = PoseidonHash>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    CV: CurveVar<C, C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    sk: SecretKey<C>,
+    params: Parameters<C, S>,
+    _curve_var: PhantomData<fn() -> CV>,
+}
+
+impl<C, CV, S> StreamDecryptor<C, CV, S>
+where
+    C: ProjectiveCurve,
+    C::BaseField: PrimeField,
+    C::Affine: Absorb,
+    C::BaseField: Absorb,
+    CV: CurveVar<C, C::BaseField> + AbsorbGadget<C::BaseField>,
+    S: SpongeHash<C::BaseField>,
+{
+    pub fn new(sk: SecretKey<C>, params: Parameters<C, S>) -> Self {
+        Self {
+            sk,
+            params,
+            _curve_var: PhantomData,
+        }
+    }
+
+    /// Decrypts a stream produced by [`StreamEncryptor::encrypt`], failing with `LengthMismatch`
+    /// if a block other than the last one isn't full or any block exceeds `params.n` slots.
+    pub fn decrypt(&self, blocks: &[Ciphertext<C>]) -> Result<Plaintext<C>, EncryptionError> {
+        let mut msg = Vec::with_capacity(blocks.len() * self.params.n);
+        for (i, block) in blocks.iter().enumerate() {
+            let is_last = i + 1 == blocks.len();
+            if block.1.len() > self.params.n
+                || (!is_last && block.1.len() != self.params.n)
+                || block.1.is_empty()
+            {
+                return Err(EncryptionError::LengthMismatch {
+                    expected: self.params.n,
+                    got: block.1.len(),
+                });
+            }
+            msg.extend(EncryptCircuit::<C, CV, S>::decrypt(
+                block.clone(),
+                &self.sk,
+                &self.params,
+            )?);
+        }
+        Ok(msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::tests::{message, Circuit};
+    use crate::{JubJub, JubJubVar};
+    use ark_ff::Zero;
+    use ark_std::test_rng;
+
+    #[test]
+    fn long_messages_round_trip_block_by_block() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let encryptor = StreamEncryptor::<JubJub, JubJubVar>::new(pk, params.clone()).unwrap();
+        let decryptor = StreamDecryptor::<JubJub, JubJubVar>::new(sk, params.clone());
+
+        for len in [0, 1, 3, 10, 12] {
+            let msg = message(len);
+            let blocks = encryptor.encrypt(&msg, &mut rng).unwrap();
+            assert_eq!(blocks.len(), len.div_ceil(3));
+            // the last block only holds the remaining slots
+            if let Some(last) = blocks.last() {
+                assert_eq!(last.1.len(), (len - 1) % 3 + 1);
+            }
+            assert_eq!(decryptor.decrypt(&blocks).unwrap(), msg);
+        }
+    }
+
+    #[test]
+    fn malformed_streams_are_rejected() {
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(3);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let encryptor = StreamEncryptor::<JubJub, JubJubVar>::new(pk, params.clone()).unwrap();
+        let decryptor = StreamDecryptor::<JubJub, JubJubVar>::new(sk, params.clone());
+        let blocks = encryptor.encrypt(&message(7), &mut rng).unwrap();
+
+        // a partial block anywhere but at the end, and an empty block
+        let reordered = [blocks[2].clone(), blocks[0].clone()];
+        let with_empty = [blocks[0].clone(), (blocks[1].0, Vec::new())];
+        for stream in [&reordered[..], &with_empty[..]] {
+            assert!(matches!(
+                decryptor.decrypt(stream),
+                Err(EncryptionError::LengthMismatch { expected: 3, .. })
+            ));
+        }
+
+        assert!(matches!(
+            StreamEncryptor::<JubJub, JubJubVar>::new(pk, Parameters::default_multi(0)),
+            Err(EncryptionError::InvalidParameters(_))
+        ));
+        assert!(matches!(
+            StreamEncryptor::<JubJub, JubJubVar>::new(JubJub::zero(), params),
+            Err(EncryptionError::IdentityPoint)
+        ));
+    }
+}
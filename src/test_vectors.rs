@@ -0,0 +1,118 @@
+//! Known-answer vectors for [`EncryptCircuit`] over JubJub with the default Poseidon
+//! parameters of [`Parameters::default_multi`], for checking other implementations of the
+//! scheme against this one.
+//!
+//! Scalars and points are hex encoded like [`crate::keys`] does, plaintext slots are decimal
+//! and the ciphertext is the hex of [`EncryptCircuit::ciphertext_to_bytes`].
+
+use crate::circuit::{EncryptCircuit, Parameters, Plaintext, Randomness};
+use crate::keys::{public_key_from_hex, secret_key_from_hex};
+use crate::{EncryptionError, JubJub, JubJubVar};
+use ark_ed_on_bls12_381::Fq;
+use ark_std::str::FromStr;
+
+/// Inputs and expected output of one encryption.
+#[derive(Clone, Copy, Debug)]
+pub struct TestVector {
+    /// Number of slots `n` of the parameters.
+    pub n: usize,
+    pub secret_key: &'static str,
+    /// `secret_key * g` for the prime-subgroup generator `g`.
+    pub public_key: &'static str,
+    pub randomness: &'static str,
+    pub plaintext: &'static [&'static str],
+    pub ciphertext: &'static str,
+}
+
+pub const JUBJUB_TEST_VECTORS: &[TestVector] = &[
+    TestVector {
+        n: 1,
+        secret_key: "0x2a00000000000000000000000000000000000000000000000000000000000000",
+        public_key: "0xd651f0d6eff14af2912a78fcea3998f3c6322053f34a7a333881775952bcfec8",
+        randomness: "0x0700000000000000000000000000000000000000000000000000000000000000",
+        plaintext: &["1"],
+        ciphertext: concat!(
+            "0x1f389920e433c12c276c99660b58a99dea054c7ae79428f890001be246469cea",
+            "0100000000000000",
+            "71a4cb16e770744e4b7135015d4c81573dd09865986abd7a387380d4881c7016",
+        ),
+    },
+    TestVector {
+        n: 3,
+        secret_key: "0x15cd5b0700000000000000000000000000000000000000000000000000000000",
+        public_key: "0xaf9715c0cce3140996c422a69e0c313b96ec8fcf2db7f77004e5c087e75f661b",
+        randomness: "0xb168de3a00000000000000000000000000000000000000000000000000000000",
+        plaintext: &[
+            "0",
+            "1",
+            "52435875175126190479447740508185965837690552500527637822603658699938581184512",
+        ],
+        ciphertext: concat!(
+            "0x1dbfa1b1710a852dc3dc25ade1da5555900f32aee8086ffa80f4cc1fe38e47ba",
+            "0300000000000000",
+            "c668abb51ab56085369f75159f073e3edfef00d78da045249f3c79f340b14873",
+            "952075e98a5406b694a4072d0e31a2bd64973a9be757b0f60d79478dc9dc536c",
+            "d10eb93484594074389a397b5be5792db187b2951d3774dde57232cc51010070",
+        ),
+    },
+];
+
+impl TestVector {
+    /// Checks that the public key matches the secret key, that encrypting the plaintext under
+    /// the randomness yields exactly the ciphertext and that decrypting it yields the plaintext
+    /// back. Fails if the vector itself is malformed.
+    pub fn check(&self) -> Result<bool, EncryptionError> {
+        type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+
+        let params = Parameters::<JubJub>::default_multi(self.n);
+        let sk = secret_key_from_hex::<JubJub>(self.secret_key)?;
+        let pk = public_key_from_hex::<JubJub>(self.public_key)?;
+        let r = Randomness::from_scalar(secret_key_from_hex::<JubJub>(self.randomness)?.0);
+        let msg = self
+            .plaintext
+            .iter()
+            .map(|m| Fq::from_str(m))
+            .collect::<Result<Plaintext<JubJub>, _>>()
+            .map_err(|_| EncryptionError::InvalidParameters("malformed plaintext slot".into()))?;
+        let expected = self
+            .ciphertext
+            .strip_prefix("0x")
+            .and_then(|s| hex::decode(s).ok())
+            .ok_or(EncryptionError::InvalidHex)?;
+
+        let cipher = Circuit::encrypt(&pk, &msg, &r, &params)?;
+        Ok(Circuit::verify_keypair(&sk, &pk)
+            && Circuit::ciphertext_to_bytes(&cipher) == expected
+            && Circuit::decrypt(cipher, &sk, &params)? == msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vectors_reproduce_their_ciphertexts() {
+        type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+
+        for vector in JUBJUB_TEST_VECTORS {
+            let params = Parameters::<JubJub>::default_multi(vector.n);
+            let sk = secret_key_from_hex::<JubJub>(vector.secret_key).unwrap();
+            let pk = public_key_from_hex::<JubJub>(vector.public_key).unwrap();
+            let r = Randomness::from_scalar(
+                secret_key_from_hex::<JubJub>(vector.randomness).unwrap().0,
+            );
+            let msg = vector
+                .plaintext
+                .iter()
+                .map(|m| Fq::from_str(m).unwrap())
+                .collect::<Plaintext<JubJub>>();
+
+            let cipher = Circuit::encrypt(&pk, &msg, &r, &params).unwrap();
+            let bytes = Circuit::ciphertext_to_bytes(&cipher);
+            assert_eq!(format!("0x{}", hex::encode(bytes)), vector.ciphertext);
+            assert_eq!(Circuit::decrypt(cipher, &sk, &params).unwrap(), msg);
+            assert!(vector.check().unwrap());
+        }
+    }
+}
@@ -0,0 +1,156 @@
+//! `t`-of-`n` threshold decryption by Shamir secret sharing of the secret key.
+//!
+//! Each party multiplies `c1` by its share, and any `t` of these partial decryptions are
+//! interpolated in the exponent into the shared point `s = sk * c1`, so the secret key itself
+//! is never reconstructed.
+
+use crate::circuit::SecretKey;
+use crate::EncryptionError;
+use ark_ec::ProjectiveCurve;
+use ark_ff::{Field, One, Zero};
+use ark_std::rand::Rng;
+use ark_std::vec::Vec;
+use ark_std::{format, UniformRand};
+
+#[cfg(feature = "zeroize")]
+use zeroize::Zeroize;
+
+/// Share of a secret key: the evaluation of the sharing polynomial at `index`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyShare<C: ProjectiveCurve> {
+    pub index: u64,
+    pub value: C::ScalarField,
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: ProjectiveCurve> Zeroize for KeyShare<C> {
+    fn zeroize(&mut self) {
+        self.value.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl<C: ProjectiveCurve> Drop for KeyShare<C> {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// Splits `sk` into `n` shares at indices `1..=n`, any `t` of which determine it.
+pub fn split_secret_key<C: ProjectiveCurve, R: Rng>(
+    sk: &SecretKey<C>,
+    t: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<KeyShare<C>>, EncryptionError> {
+    if t == 0 || t > n {
+        return Err(EncryptionError::InvalidParameters(format!(
+            "threshold {t} must be between 1 and the number of shares {n}"
+        )));
+    }
+
+    // f(x) = sk + a_1 x + ... + a_{t-1} x^{t-1}
+    let coeffs = ark_std::iter::once(sk.0)
+        .chain((1..t).map(|_| C::ScalarField::rand(rng)))
+        .collect::<Vec<_>>();
+
+    Ok((1..=n as u64)
+        .map(|index| {
+            let x = C::ScalarField::from(index);
+            let value = coeffs
+                .iter()
+                .rev()
+                .fold(C::ScalarField::zero(), |acc, c| acc * x + c);
+            KeyShare { index, value }
+        })
+        .collect())
+}
+
+/// Computes the partial decryption `s_i = share_i * c1`.
+pub fn partial_decrypt<C: ProjectiveCurve>(share: &KeyShare<C>, c1: &C) -> C {
+    let mut s = *c1;
+    s.mul_assign(share.value);
+    s
+}
+
+/// Interpolates the partial decryptions of the parties at `indices` into the shared point
+/// `s = sk * c1`, to be used with [`crate::EncryptCircuit::decrypt_with_shared_point`].
+///
+/// Fewer than `t` partials interpolate to an unrelated point rather than failing.
+pub fn combine_partials<C: ProjectiveCurve>(
+    partials: &[C],
+    indices: &[u64],
+) -> Result<C, EncryptionError> {
+    if partials.len() != indices.len() {
+        return Err(EncryptionError::LengthMismatch {
+            expected: indices.len(),
+            got: partials.len(),
+        });
+    }
+    if indices.contains(&0) {
+        return Err(EncryptionError::InvalidParameters(
+            "share indices must be non-zero".into(),
+        ));
+    }
+
+    let xs = indices
+        .iter()
+        .map(|&i| C::ScalarField::from(i))
+        .collect::<Vec<_>>();
+
+    xs.iter()
+        .zip(partials)
+        .enumerate()
+        .try_fold(C::zero(), |acc, (i, (xi, si))| {
+            // Lagrange basis polynomial for x_i evaluated at 0, a repeated index zeroes `den`
+            let (num, den) = xs.iter().enumerate().filter(|(j, _)| *j != i).fold(
+                (C::ScalarField::one(), C::ScalarField::one()),
+                |(num, den), (_, xj)| (num * xj, den * (*xj - xi)),
+            );
+            let lambda = den.inverse().ok_or_else(|| {
+                EncryptionError::InvalidParameters("share indices must be distinct".into())
+            })? * num;
+            let mut term = *si;
+            term.mul_assign(lambda);
+            Ok(acc + term)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuit::{Parameters, Randomness};
+    use crate::{EncryptCircuit, JubJub, JubJubVar};
+    use ark_ed_on_bls12_381::Fq;
+    use ark_std::test_rng;
+
+    #[test]
+    fn threshold_partials_recover_the_plaintext() {
+        type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+
+        let mut rng = test_rng();
+        let params = Parameters::<JubJub>::default_multi(2);
+        let (sk, pk) = Circuit::keygen(&mut rng).unwrap();
+        let msg = vec![Fq::from(100u64), Fq::from(200u64)];
+        let cipher = Circuit::encrypt(&pk, &msg, &Randomness::rand(&mut rng), &params).unwrap();
+
+        let shares = split_secret_key(&sk, 3, 5, &mut rng).unwrap();
+        let decrypt_with = |shares: &[KeyShare<JubJub>]| {
+            let partials = shares
+                .iter()
+                .map(|share| partial_decrypt(share, &cipher.0))
+                .collect::<Vec<_>>();
+            let indices = shares.iter().map(|share| share.index).collect::<Vec<_>>();
+            let s = combine_partials(&partials, &indices).unwrap();
+            Circuit::decrypt_with_shared_point(cipher.clone(), &s, &params).unwrap()
+        };
+
+        assert_eq!(decrypt_with(&shares[..3]), msg);
+        assert_eq!(
+            decrypt_with(&[shares[4].clone(), shares[1].clone(), shares[3].clone()]),
+            msg
+        );
+        assert_ne!(decrypt_with(&shares[..2]), msg);
+        assert!(split_secret_key(&sk, 6, 5, &mut rng).is_err());
+    }
+}
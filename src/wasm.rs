@@ -0,0 +1,148 @@
+//! JavaScript bindings for the JubJub instantiation, enabled by the `wasm` feature.
+//!
+//! Keys are hex-encoded canonical serializations, with public keys in compressed form,
+//! plaintexts are arrays of field elements as decimal strings and ciphertexts are the JSON form
+//! of [`SerializableCiphertext`]. Browsers don't provide threads, so builds targeting them
+//! should leave out the default `parallel` feature, e.g. `--no-default-features --features
+//! r1cs,wasm`.
+
+use crate::serialization::{field_from_bytes, field_to_bytes, point_from_bytes, point_to_bytes};
+use crate::{
+    EncryptCircuit, JubJub, JubJubVar, Parameters, Randomness, SecretKey, SerializableCiphertext,
+};
+use ark_ec::ProjectiveCurve;
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::rand::SeedableRng;
+use ark_std::str::FromStr;
+use ark_std::UniformRand;
+use num_bigint::BigUint;
+use rand_chacha::ChaCha20Rng;
+use wasm_bindgen::prelude::*;
+
+type Circuit = EncryptCircuit<JubJub, JubJubVar>;
+type Fq = <JubJub as ProjectiveCurve>::BaseField;
+type Fr = <JubJub as ProjectiveCurve>::ScalarField;
+
+#[wasm_bindgen]
+pub struct KeyPair {
+    secret_key: String,
+    public_key: String,
+}
+
+#[wasm_bindgen]
+impl KeyPair {
+    #[wasm_bindgen(getter, js_name = secretKey)]
+    pub fn secret_key(&self) -> String {
+        self.secret_key.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = publicKey)]
+    pub fn public_key(&self) -> String {
+        self.public_key.clone()
+    }
+}
+
+#[wasm_bindgen]
+pub fn keygen() -> Result<KeyPair, JsError> {
+    let (sk, pk) = Circuit::keygen(&mut entropy_rng()?)?;
+
+    Ok(KeyPair {
+        secret_key: hex::encode(field_to_bytes(&sk.0)),
+        public_key: hex::encode(point_to_bytes(&pk)),
+    })
+}
+
+/// Encrypts `plaintext` to the hex-encoded `public_key` and returns the ciphertext as JSON.
+#[wasm_bindgen]
+pub fn encrypt(public_key: &str, plaintext: Vec<String>) -> Result<String, JsError> {
+    let pk = parse_public_key(public_key).ok_or_else(|| JsError::new("invalid public key"))?;
+    let msg =
+        parse_plaintext(&plaintext).ok_or_else(|| JsError::new("invalid plaintext element"))?;
+
+    let params = Parameters::<JubJub>::default_multi(msg.len());
+    let r = Randomness::rand(&mut entropy_rng()?);
+    let cipher = Circuit::encrypt(&pk, &msg, &r, &params)?;
+
+    Ok(serde_json::to_string(&SerializableCiphertext::from(
+        cipher,
+    ))?)
+}
+
+/// Decrypts the JSON `ciphertext` with the hex-encoded `secret_key` into decimal strings.
+#[wasm_bindgen]
+pub fn decrypt(secret_key: &str, ciphertext: &str) -> Result<Vec<String>, JsError> {
+    let sk = parse_secret_key(secret_key).ok_or_else(|| JsError::new("invalid secret key"))?;
+    let cipher = serde_json::from_str::<SerializableCiphertext<JubJub>>(ciphertext)?.0;
+
+    let params = Parameters::<JubJub>::default_multi(cipher.1.len());
+    let msg = Circuit::decrypt(cipher, &sk, &params)?;
+
+    Ok(msg
+        .iter()
+        .map(|m| BigUint::from_bytes_le(&m.into_repr().to_bytes_le()).to_string())
+        .collect())
+}
+
+// the parsers return `None` rather than a `JsError`, which can only be created on wasm targets
+fn parse_public_key(public_key: &str) -> Option<JubJub> {
+    hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| point_from_bytes::<JubJub>(&bytes))
+}
+
+fn parse_secret_key(secret_key: &str) -> Option<SecretKey<JubJub>> {
+    hex::decode(secret_key)
+        .ok()
+        .and_then(|bytes| field_from_bytes::<Fr>(&bytes))
+        .map(SecretKey)
+}
+
+fn parse_plaintext(plaintext: &[String]) -> Option<Vec<Fq>> {
+    plaintext.iter().map(|m| Fq::from_str(m).ok()).collect()
+}
+
+// seeds ChaCha20 from the platform's entropy source, `crypto.getRandomValues` in browsers
+fn entropy_rng() -> Result<ChaCha20Rng, JsError> {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).map_err(|err| JsError::new(&err.to_string()))?;
+    Ok(ChaCha20Rng::from_seed(seed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_std::{string::ToString, vec};
+
+    #[test]
+    fn wasm_bindings_round_trip_a_plaintext() {
+        let keys = keygen().unwrap();
+        let minus_one = BigUint::from_bytes_le(&(-Fq::from(1u64)).into_repr().to_bytes_le());
+        let plaintext = vec!["0".to_string(), "42".to_string(), minus_one.to_string()];
+        assert_eq!(parse_plaintext(&plaintext).unwrap()[2], -Fq::from(1u64));
+
+        let ciphertext = encrypt(&keys.public_key(), plaintext.clone()).unwrap();
+        let decrypted = decrypt(&keys.secret_key(), &ciphertext).unwrap();
+        assert_eq!(decrypted, plaintext);
+
+        let other = keygen().unwrap();
+        assert_ne!(
+            decrypt(&other.secret_key(), &ciphertext).unwrap(),
+            decrypted
+        );
+    }
+
+    #[test]
+    fn wasm_bindings_reject_malformed_inputs() {
+        let keys = keygen().unwrap();
+        assert!(parse_public_key(&keys.public_key()).is_some());
+        assert!(parse_secret_key(&keys.secret_key()).is_some());
+
+        for invalid in ["", "0x00", "zz", &keys.public_key()[1..], &"ff".repeat(32)] {
+            assert!(parse_public_key(invalid).is_none());
+        }
+        assert!(parse_secret_key("not hex").is_none());
+        assert!(parse_secret_key(&"ff".repeat(32)).is_none());
+        assert!(parse_plaintext(&["1".to_string(), "x".to_string()]).is_none());
+        assert!(parse_plaintext(&["-1".to_string()]).is_none());
+    }
+}